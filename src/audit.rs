@@ -0,0 +1,111 @@
+//! `--audit-deps` & `--export-deps` workspace dependency reports.
+use cargo_metadata::Metadata;
+use cargo_toml::Manifest;
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+
+/// Print each workspace dependency with the versions of that crate present in the
+/// resolved graph, grouped by whether other (non-member) dependencies also use it.
+pub fn audit_workspace_deps(root: &Manifest, meta: &Metadata) -> anyhow::Result<()> {
+    let Some(resolve) = &meta.resolve else {
+        anyhow::bail!("--audit-deps requires resolved metadata, it cannot be used with --no-deps");
+    };
+    let Some(ws) = &root.workspace else {
+        eprintln!("No [workspace.dependencies] to audit");
+        return Ok(());
+    };
+
+    let package_names: HashMap<_, _> = meta
+        .packages
+        .iter()
+        .map(|p| (&p.id, p.name.as_str()))
+        .collect();
+    // crates depended on by non-member packages
+    let used_transitively: HashSet<_> = resolve
+        .nodes
+        .iter()
+        .filter(|n| !meta.workspace_members.contains(&n.id))
+        .flat_map(|n| &n.dependencies)
+        .filter_map(|d| package_names.get(d).copied())
+        .collect();
+
+    let (mut transitive, mut direct) = (vec![], vec![]);
+    for (key, dep) in &ws.dependencies {
+        let name = dep.package().unwrap_or(key);
+        let mut versions: Vec<_> = meta
+            .packages
+            .iter()
+            .filter(|p| p.name == name)
+            .map(|p| &p.version)
+            .collect();
+        versions.sort();
+        versions.dedup();
+
+        match used_transitively.contains(name) {
+            true => transitive.push((key, versions)),
+            false => direct.push((key, versions)),
+        }
+    }
+
+    for (title, deps) in [
+        ("Also used transitively", transitive),
+        ("Direct only", direct),
+    ] {
+        if deps.is_empty() {
+            continue;
+        }
+        println!("{}", title.bold());
+        for (dep, versions) in deps {
+            if versions.is_empty() {
+                println!("  {dep} {}", "not in the dependency graph".dimmed());
+                continue;
+            }
+            let versions_str = versions
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let line = format!("  {dep} {} version(s): {versions_str}", versions.len());
+            match versions.len() > 1 {
+                true => println!("{}", line.yellow()),
+                false => println!("{line}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Csv of each workspace dependency & the members using it, with columns
+/// `dependency,workspace-version,used-by-count,members,features`.
+///
+/// `members` & `features` are `;` separated.
+pub fn workspace_deps_csv(root: &Manifest, members: &[Manifest]) -> String {
+    let mut csv = String::from("dependency,workspace-version,used-by-count,members,features\n");
+    for (name, dep) in root.workspace.iter().flat_map(|ws| &ws.dependencies) {
+        let users: Vec<_> = members
+            .iter()
+            .filter(|m| crate::dependency_tables(m).any(|t| t.deps.contains_key(name)))
+            .map(|m| m.package.as_ref().map_or("?", |p| p.name()))
+            .collect();
+        let row = [
+            name.as_str(),
+            dep.try_req().unwrap_or(""),
+            &users.len().to_string(),
+            &users.join(";"),
+            &dep.req_features().join(";"),
+        ]
+        .map(csv_field)
+        .join(",");
+        csv += &row;
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote the field if it contains `,`, `"` or newlines.
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned(),
+    }
+}
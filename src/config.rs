@@ -0,0 +1,204 @@
+//! Configuration from the workspace root manifest `[workspace.metadata.ab-lint]`,
+//! merged over the user config `ab-lint/config.toml` in the user config dir.
+use anyhow::Context;
+use fs_err as fs;
+use std::{collections::BTreeMap, env, fmt, path::PathBuf};
+
+/// Where a setting came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    User,
+    Workspace,
+    Cli,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::User => "user",
+            Self::Workspace => "workspace",
+            Self::Cli => "cli",
+        })
+    }
+}
+
+/// Supported keys.
+const KEYS: [&str; 5] = [
+    "dependency-order",
+    "inherit-member-deps",
+    "publishable-default-members",
+    "format",
+    "dev-dependencies",
+];
+
+#[derive(Debug, Default)]
+pub struct Config {
+    /// `dependency-order` key prefixes sorted first, in order, then alphabetical.
+    /// Enables the dependency order lint if set.
+    pub dependency_order: Option<Vec<String>>,
+    /// `inherit-member-deps` requires dependencies on other members to use workspace inheritance.
+    pub inherit_member_deps: Option<bool>,
+    /// `publishable-default-members` reports `publish = false` members in `default-members`.
+    pub publishable_default_members: Option<bool>,
+    /// Default `--format`.
+    pub format: Option<String>,
+    /// `dev-dependencies = false` doesn't lint member dev-dependencies, like `--no-dev`.
+    pub dev_dependencies: Option<bool>,
+    /// Source of each set key.
+    sources: BTreeMap<&'static str, Source>,
+}
+
+impl Config {
+    /// Workspace config, falling back to the user config for unset keys.
+    pub fn load(root_doc: &toml_edit::DocumentMut) -> anyhow::Result<Self> {
+        let workspace = match root_doc
+            .get("workspace")
+            .and_then(|ws| ws.get("metadata"))
+            .and_then(|m| m.get("ab-lint"))
+        {
+            Some(table) => Self::from_item(table, Source::Workspace)
+                .context("invalid [workspace.metadata.ab-lint]")?,
+            None => Self::default(),
+        };
+
+        let user = match user_config_path().filter(|p| p.exists()) {
+            Some(path) => {
+                log::debug!("reading user config {}", path.display());
+                let toml = fs::read_to_string(&path)?;
+                let doc = toml
+                    .parse::<toml_edit::DocumentMut>()
+                    .with_context(|| format!("{}", path.display()))?;
+                Self::from_item(doc.as_item(), Source::User)
+                    .with_context(|| format!("{}", path.display()))?
+            }
+            None => Self::default(),
+        };
+
+        Ok(workspace.or(user))
+    }
+
+    fn from_item(table: &toml_edit::Item, source: Source) -> anyhow::Result<Self> {
+        let dependency_order = table
+            .get("dependency-order")
+            .map(|order| string_array(order).context("dependency-order"))
+            .transpose()?;
+
+        let inherit_member_deps = table
+            .get("inherit-member-deps")
+            .map(|v| v.as_bool().context("inherit-member-deps: expected a bool"))
+            .transpose()?;
+
+        let publishable_default_members = table
+            .get("publishable-default-members")
+            .map(|v| {
+                v.as_bool()
+                    .context("publishable-default-members: expected a bool")
+            })
+            .transpose()?;
+
+        let format = table
+            .get("format")
+            .map(|v| {
+                v.as_str()
+                    .map(String::from)
+                    .context("format: expected a string")
+            })
+            .transpose()?;
+
+        let dev_dependencies = table
+            .get("dev-dependencies")
+            .map(|v| v.as_bool().context("dev-dependencies: expected a bool"))
+            .transpose()?;
+
+        let sources = KEYS
+            .into_iter()
+            .filter(|key| table.get(key).is_some())
+            .map(|key| (key, source))
+            .collect();
+
+        Ok(Self {
+            dependency_order,
+            inherit_member_deps,
+            publishable_default_members,
+            format,
+            dev_dependencies,
+            sources,
+        })
+    }
+
+    /// Keys set in `self`, otherwise from `fallback`.
+    fn or(self, mut fallback: Self) -> Self {
+        fallback.sources.extend(self.sources);
+        Self {
+            dependency_order: self.dependency_order.or(fallback.dependency_order),
+            inherit_member_deps: self.inherit_member_deps.or(fallback.inherit_member_deps),
+            publishable_default_members: self
+                .publishable_default_members
+                .or(fallback.publishable_default_members),
+            format: self.format.or(fallback.format),
+            dev_dependencies: self.dev_dependencies.or(fallback.dev_dependencies),
+            sources: fallback.sources,
+        }
+    }
+
+    /// Print the effective config as toml, commenting the source of each setting.
+    ///
+    /// `cli_format` overrides the configured format.
+    pub fn print(&self, cli_format: Option<&str>, cli_include_dev: Option<bool>) {
+        let source_of = |key: &str| self.sources.get(key).copied().unwrap_or(Source::Default);
+
+        let (format, format_source) = match (cli_format, &self.format) {
+            (Some(f), _) => (f, Source::Cli),
+            (None, Some(f)) => (f.as_str(), source_of("format")),
+            (None, None) => ("human", Source::Default),
+        };
+        println!("format = {format:?} # {format_source}");
+
+        match &self.dependency_order {
+            Some(order) => {
+                let order = toml_edit::Array::from_iter(order);
+                let src = source_of("dependency-order");
+                println!("dependency-order = {order} # {src}");
+            }
+            None => println!("# dependency-order unset, lint disabled # default"),
+        }
+
+        let inherit = self.inherit_member_deps.unwrap_or_default();
+        let src = source_of("inherit-member-deps");
+        println!("inherit-member-deps = {inherit} # {src}");
+
+        let publishable = self.publishable_default_members.unwrap_or_default();
+        let src = source_of("publishable-default-members");
+        println!("publishable-default-members = {publishable} # {src}");
+
+        let (dev, dev_source) = match (cli_include_dev, self.dev_dependencies) {
+            (Some(dev), _) => (dev, Source::Cli),
+            (None, dev) => (dev.unwrap_or(true), source_of("dev-dependencies")),
+        };
+        println!("dev-dependencies = {dev} # {dev_source}");
+    }
+}
+
+/// `$XDG_CONFIG_HOME/ab-lint/config.toml`, `~/.config/ab-lint/config.toml`
+/// or `%APPDATA%\ab-lint\config.toml` on windows.
+fn user_config_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| match cfg!(windows) {
+            true => env::var_os("APPDATA").map(PathBuf::from),
+            false => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+        })?;
+    Some(dir.join("ab-lint").join("config.toml"))
+}
+
+fn string_array(item: &toml_edit::Item) -> anyhow::Result<Vec<String>> {
+    let array = item.as_array().context("expected an array of strings")?;
+    array
+        .iter()
+        .map(|v| v.as_str().map(|s| s.to_owned()))
+        .collect::<Option<_>>()
+        .context("expected an array of strings")
+}
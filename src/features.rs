@@ -0,0 +1,284 @@
+//! Dependency feature lints, mostly `--check-features` lints using dependency features
+//! from `cargo metadata`.
+use crate::{finding::Finding, DepTable};
+use cargo_metadata::{Metadata, Package};
+use colored::Colorize;
+
+pub const IMPLIED_DEPENDENCY_FEATURES: &str = "implied-dependency-features";
+pub const SELF_NAMED_FEATURE: &str = "self-named-dependency-feature";
+pub const DISABLED_DEFAULT_DEPENDENCY: &str = "disabled-default-dependency";
+
+/// Resolved metadata of the packages a member depends on.
+pub struct DependencyFeatures<'a> {
+    meta: &'a Metadata,
+    member: &'a Package,
+}
+
+impl<'a> DependencyFeatures<'a> {
+    /// Returns `None` if the member package isn't in the metadata.
+    pub fn new(meta: &'a Metadata, member: &cargo_metadata::PackageId) -> Option<Self> {
+        let member = meta.packages.iter().find(|p| p.id == *member)?;
+        Some(Self { meta, member })
+    }
+
+    /// The package a member dependency `key` resolves to, highest matching version.
+    ///
+    /// `None` if unavailable, e.g. when running with `--no-deps`.
+    fn package(&self, key: &str) -> Option<&'a Package> {
+        let dep = self
+            .member
+            .dependencies
+            .iter()
+            .find(|d| d.rename.as_deref().unwrap_or(&d.name) == key)?;
+        self.meta
+            .packages
+            .iter()
+            .filter(|p| p.name == dep.name && dep.req.matches(&p.version))
+            .max_by(|a, b| a.version.cmp(&b.version))
+    }
+}
+
+/// Dependency features equal to the crate name, e.g. `serde = { features = ["serde"] }`.
+///
+/// With `dep_features` only reported if the resolved crate has no such feature,
+/// otherwise a heuristic.
+pub fn self_named_features(
+    table: &DepTable<'_>,
+    dep_features: Option<&DependencyFeatures<'_>>,
+    findings: &mut Vec<Finding>,
+) {
+    for (name, dep) in table.deps {
+        let crate_name = dep.package().unwrap_or(name);
+        if !dep.req_features().iter().any(|f| f == crate_name) {
+            continue;
+        }
+        let message = match dep_features.and_then(|d| d.package(name)) {
+            Some(package) if package.features.contains_key(crate_name) => continue,
+            Some(_) => format!(
+                "Feature {} of {} {} doesn't exist, the crate name isn't a feature",
+                crate_name.bold(),
+                table.item_name,
+                name.bold(),
+            ),
+            None => format!(
+                "Feature {} of {} {} is the crate name, likely a mistake",
+                crate_name.bold(),
+                table.item_name,
+                name.bold(),
+            ),
+        };
+        findings.push(Finding::new(SELF_NAMED_FEATURE, message).at(table.keys_to(name)));
+    }
+}
+
+/// Dependency features already enabled, directly or transitively, by another listed feature,
+/// e.g. `["full", "rt"]` where `full = ["rt", ...]`.
+pub fn implied_dependency_features(
+    table: &DepTable<'_>,
+    dep_features: &DependencyFeatures<'_>,
+    doc: &mut toml_edit::DocumentMut,
+    findings: &mut Vec<Finding>,
+) {
+    for (name, dep) in table.deps {
+        let listed = dep.req_features();
+        if listed.len() < 2 {
+            continue;
+        }
+        let Some(package) = dep_features.package(name) else {
+            continue;
+        };
+
+        let mut kept: Vec<&str> = listed.iter().map(|f| f.as_str()).collect();
+        let mut implied = vec![];
+        for feature in listed {
+            let implied_by = kept
+                .iter()
+                .copied()
+                .find(|other| *other != feature && enables(package, other, feature));
+            if let Some(by) = implied_by {
+                kept.retain(|f| f != feature);
+                implied.push((feature.as_str(), by));
+            }
+        }
+        if implied.is_empty() {
+            continue;
+        }
+
+        let keys = table.keys_to(name);
+        for (feature, by) in &implied {
+            findings.push(
+                Finding::new(
+                    IMPLIED_DEPENDENCY_FEATURES,
+                    format!(
+                        "Feature {} of {} {} is already enabled by {}",
+                        feature.bold(),
+                        table.item_name,
+                        name.bold(),
+                        by.bold(),
+                    ),
+                )
+                .removing_features([feature])
+                .at(&keys),
+            );
+        }
+
+        if let Some(feats) = crate::doc_item_mut(doc, &keys)
+            .and_then(|dep| dep.as_table_like_mut()?.get_mut("features"))
+            .and_then(|f| f.as_array_mut())
+        {
+            feats.retain(|v| !v.as_str().is_some_and(|f| implied.iter().any(|i| i.0 == f)));
+        }
+    }
+}
+
+/// Features listed with `default-features = false` that enable a weak `dep?/feature` where the
+/// optional `dep` is only enabled by the disabled default features, so has no effect.
+pub fn features_without_default_dependency(
+    table: &DepTable<'_>,
+    dep_features: &DependencyFeatures<'_>,
+    findings: &mut Vec<Finding>,
+) {
+    for (name, dep) in table.deps {
+        let Some(detail) = dep.detail() else {
+            continue;
+        };
+        if detail.default_features || detail.features.is_empty() {
+            continue;
+        }
+        let Some(package) = dep_features.package(name) else {
+            continue;
+        };
+
+        let enabled = enabled_features(package, detail.features.iter().map(String::as_str));
+        let by_default = enabled_features(package, ["default"]);
+        let enables_dep = |features: &[&str], opt_dep: &str| {
+            features.iter().any(|f| {
+                *f == opt_dep
+                    || f.strip_prefix("dep:") == Some(opt_dep)
+                    || f.split_once('/').is_some_and(|(d, _)| d == opt_dep)
+            })
+        };
+
+        for feature in &detail.features {
+            for weak in enabled_features(package, [feature.as_str()]) {
+                let Some((opt_dep, _)) = weak.split_once("?/") else {
+                    continue;
+                };
+                if !enables_dep(&by_default, opt_dep) || enables_dep(&enabled, opt_dep) {
+                    continue;
+                }
+                findings.push(
+                    Finding::new(
+                        DISABLED_DEFAULT_DEPENDENCY,
+                        format!(
+                            "Feature {} of {} {} enables {} but {} is only enabled by default \
+                             features, disabled by default-features = false",
+                            feature.bold(),
+                            table.item_name,
+                            name.bold(),
+                            weak.bold(),
+                            opt_dep.bold(),
+                        ),
+                    )
+                    .at(table.keys_to(name)),
+                );
+            }
+        }
+    }
+}
+
+/// The package `features` & all features they enable, transitively.
+fn enabled_features<'a>(
+    package: &'a Package,
+    features: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let mut stack: Vec<_> = features.into_iter().collect();
+    let mut seen = vec![];
+    while let Some(f) = stack.pop() {
+        if seen.contains(&f) {
+            continue;
+        }
+        seen.push(f);
+        stack.extend(
+            package
+                .features
+                .get(f)
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+        );
+    }
+    seen
+}
+
+/// Whether the package `feature` enables `target`, directly or transitively.
+fn enables(package: &Package, feature: &str, target: &str) -> bool {
+    let mut stack = vec![feature];
+    let mut seen = vec![];
+    while let Some(f) = stack.pop() {
+        if seen.contains(&f) {
+            continue;
+        }
+        seen.push(f);
+        for enabled in package.features.get(f).into_iter().flatten() {
+            if enabled == target {
+                return true;
+            }
+            stack.push(enabled);
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(features: serde_json::Value) -> Package {
+        serde_json::from_value(serde_json::json!({
+            "name": "dep",
+            "version": "1.0.0",
+            "id": "dep 1.0.0",
+            "dependencies": [],
+            "targets": [],
+            "features": features,
+            "manifest_path": "dep/Cargo.toml",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn enables_transitively() {
+        let package = package(serde_json::json!({
+            "full": ["net", "fs"],
+            "net": ["rt", "dep:socket2"],
+            "fs": [],
+            "rt": [],
+            // cycles are possible
+            "a": ["b"],
+            "b": ["a"],
+        }));
+        assert!(enables(&package, "full", "net"));
+        assert!(enables(&package, "full", "rt"));
+        assert!(enables(&package, "net", "dep:socket2"));
+        assert!(!enables(&package, "net", "fs"));
+        assert!(!enables(&package, "rt", "rt"));
+        assert!(!enables(&package, "a", "rt"));
+    }
+
+    #[test]
+    fn enabled_by_default() {
+        let package = package(serde_json::json!({
+            "default": ["std"],
+            "std": ["serde?/std"],
+            "serde": ["dep:serde"],
+        }));
+        let mut enabled = enabled_features(&package, ["default"]);
+        enabled.sort();
+        assert_eq!(enabled, ["default", "serde?/std", "std"]);
+        assert_eq!(
+            enabled_features(&package, ["serde"]),
+            ["serde", "dep:serde"]
+        );
+    }
+}
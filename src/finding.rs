@@ -0,0 +1,95 @@
+//! Lint findings & their locations.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Diagnostic only, doesn't affect the exit code.
+    Info,
+    Warning,
+    /// Invalid manifest content, e.g. breaking cargo.
+    Error,
+}
+
+/// A single lint finding in a manifest.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// Displayed manifest path, set when reported.
+    pub path: Option<String>,
+    /// Lint id, e.g. "unused-workspace-deps".
+    pub lint: &'static str,
+    pub message: String,
+    pub severity: Severity,
+    /// Whether `--fix` resolves this finding.
+    pub fixable: bool,
+    /// Toml key path locating the finding, e.g. `["dependencies", "serde"]`.
+    pub keys: Vec<String>,
+    /// 1-based line, if known. Otherwise resolved from `keys` when reported.
+    pub line: Option<usize>,
+    /// Dependency features removed by the fix, summarised together when fixing.
+    pub removed_features: Vec<String>,
+}
+
+impl Finding {
+    pub fn new(lint: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            path: None,
+            lint,
+            message: message.into(),
+            severity: Severity::Warning,
+            fixable: false,
+            keys: vec![],
+            line: None,
+            removed_features: vec![],
+        }
+    }
+
+    pub fn fixable(mut self) -> Self {
+        self.fixable = true;
+        self
+    }
+
+    pub fn info(mut self) -> Self {
+        self.severity = Severity::Info;
+        self
+    }
+
+    pub fn error(mut self) -> Self {
+        self.severity = Severity::Error;
+        self
+    }
+
+    pub fn at<K: ToString>(mut self, keys: impl IntoIterator<Item = K>) -> Self {
+        self.keys = keys.into_iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    pub fn line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Fixable finding removing dependency `features`.
+    pub fn removing_features<F: ToString>(mut self, features: impl IntoIterator<Item = F>) -> Self {
+        self.removed_features = features.into_iter().map(|f| f.to_string()).collect();
+        self.fixable()
+    }
+}
+
+/// 1-based line of the deepest existing key in `keys`.
+pub fn line_of(doc: &toml_edit::ImDocument<&str>, keys: &[String]) -> Option<usize> {
+    let mut table: &dyn toml_edit::TableLike = doc.as_table();
+    let mut offset = None;
+
+    for key in keys {
+        let Some((k, item)) = table.get_key_value(key) else {
+            break;
+        };
+        offset = k.span().or_else(|| item.span()).map(|s| s.start).or(offset);
+        match item.as_table_like() {
+            Some(t) => table = t,
+            None => break,
+        }
+    }
+
+    let offset = offset?;
+    Some(doc.raw()[..offset].matches('\n').count() + 1)
+}
@@ -1,260 +1,2832 @@
+mod audit;
+mod config;
+mod features;
+mod finding;
+mod order;
+mod patch;
+mod rejected;
+mod report;
+mod style;
+mod version;
+
 use anyhow::Context;
 use cargo_metadata::{camino::Utf8PathBuf, PackageId};
 use cargo_toml::Manifest;
 use colored::Colorize;
+use finding::Finding;
 use fs_err as fs;
+use order::DependencyOrder;
+use report::{fixed_lints, Format, Reporter};
 use std::{env, str::FromStr};
+use style::TomlStyle;
+
+const REDUNDANT_WORKSPACE_FEATURES: &str = "redundant-workspace-features";
+const REDUNDANT_DEFAULT_FEATURES: &str = "redundant-default-features";
+const UNUSED_WORKSPACE_DEPS: &str = "unused-workspace-deps";
+const UNUSED_WORKSPACE_PACKAGE: &str = "unused-workspace-package";
+const WHITESPACE: &str = "whitespace";
+const SHOULD_INHERIT: &str = "should-inherit-workspace-dep";
+const NON_OPTIONAL_DEP_FEATURE: &str = "non-optional-dep-feature";
+const CONFLICTING_GIT_REFS: &str = "conflicting-git-refs";
+const UNSATISFIABLE_VERSION: &str = "unsatisfiable-version-req";
+const REDUNDANT_AUTO_DISCOVERY: &str = "redundant-auto-discovery";
+const PATH_OUTSIDE_WORKSPACE: &str = "path-dep-outside-workspace";
+const DIVERGENT_VERSIONS: &str = "divergent-version-reqs";
+const RENAMED_DEP_FEATURE: &str = "renamed-dep-feature-ref";
+const BUILD_DEPS_WITHOUT_SCRIPT: &str = "build-deps-without-build-script";
+const MIXED_SOURCES: &str = "mixed-dependency-sources";
+const IMPLICIT_OPTIONAL_FEATURE: &str = "implicit-optional-dep-feature";
+const EMPTY_DEFAULT_MEMBERS: &str = "empty-default-members";
+const MSRV_ABOVE_TOOLCHAIN: &str = "rust-version-above-toolchain";
+const DEPENDENCY_ORDER: &str = "dependency-order";
+const SOURCELESS_WORKSPACE_DEP: &str = "workspace-dep-without-source";
+const DEPRECATED_LICENSE_SLASH: &str = "deprecated-license-slash";
+const MISNAMED_WORKSPACE_DEP: &str = "misnamed-workspace-dep";
+const INEFFECTIVE_RESOLVER: &str = "ineffective-resolver";
+const REDUNDANT_OPTIONAL_FALSE: &str = "redundant-optional-false";
+const EMPTY_DEPENDENCY_FEATURES: &str = "empty-dependency-features";
+const IGNORED_PROFILE: &str = "ignored-profile";
+const BLOATED_WORKSPACE_FEATURES: &str = "bloated-workspace-features";
+const REDUNDANT_MEMBER_PATCH: &str = "redundant-member-patch";
+const UNINHERITED_MEMBER_DEP: &str = "uninherited-member-dep";
+const DEFAULT_DOCUMENTATION: &str = "default-documentation-url";
+const CIRCULAR_FEATURES: &str = "circular-features";
+const PADDED_STRING: &str = "padded-version-string";
+const MISSING_MEMBER_MANIFEST: &str = "missing-member-manifest";
+const DEPENDENCY_ARRAY_OF_TABLES: &str = "dependency-array-of-tables";
+const SELF_DEPENDENCY: &str = "self-dependency";
+const UNPUBLISHED_DEFAULT_MEMBER: &str = "unpublished-default-member";
+const REDUNDANT_TARGET_DEP: &str = "redundant-target-dependency";
+const MEMBER_RUST_VERSION: &str = "member-rust-version";
+/// All lint ids, accepted by `--only`.
+const LINTS: &[&str] = &[
+    REDUNDANT_WORKSPACE_FEATURES,
+    REDUNDANT_DEFAULT_FEATURES,
+    UNUSED_WORKSPACE_DEPS,
+    UNUSED_WORKSPACE_PACKAGE,
+    WHITESPACE,
+    SHOULD_INHERIT,
+    NON_OPTIONAL_DEP_FEATURE,
+    CONFLICTING_GIT_REFS,
+    UNSATISFIABLE_VERSION,
+    REDUNDANT_AUTO_DISCOVERY,
+    PATH_OUTSIDE_WORKSPACE,
+    DIVERGENT_VERSIONS,
+    RENAMED_DEP_FEATURE,
+    BUILD_DEPS_WITHOUT_SCRIPT,
+    MIXED_SOURCES,
+    IMPLICIT_OPTIONAL_FEATURE,
+    EMPTY_DEFAULT_MEMBERS,
+    MSRV_ABOVE_TOOLCHAIN,
+    DEPENDENCY_ORDER,
+    SOURCELESS_WORKSPACE_DEP,
+    DEPRECATED_LICENSE_SLASH,
+    MISNAMED_WORKSPACE_DEP,
+    INEFFECTIVE_RESOLVER,
+    REDUNDANT_OPTIONAL_FALSE,
+    EMPTY_DEPENDENCY_FEATURES,
+    IGNORED_PROFILE,
+    BLOATED_WORKSPACE_FEATURES,
+    REDUNDANT_MEMBER_PATCH,
+    UNINHERITED_MEMBER_DEP,
+    DEFAULT_DOCUMENTATION,
+    CIRCULAR_FEATURES,
+    PADDED_STRING,
+    MISSING_MEMBER_MANIFEST,
+    DEPENDENCY_ARRAY_OF_TABLES,
+    SELF_DEPENDENCY,
+    UNPUBLISHED_DEFAULT_MEMBER,
+    REDUNDANT_TARGET_DEP,
+    MEMBER_RUST_VERSION,
+    features::IMPLIED_DEPENDENCY_FEATURES,
+    features::DISABLED_DEFAULT_DEPENDENCY,
+    features::SELF_NAMED_FEATURE,
+];
+/// Lints only run with a flag, implied by `--only <lint>`.
+const FLAG_LINTS: &[(&str, &str)] = &[
+    (WHITESPACE, "--check-whitespace"),
+    (MSRV_ABOVE_TOOLCHAIN, "--check-toolchain"),
+    (IMPLICIT_OPTIONAL_FEATURE, "--migration-check"),
+    (MISNAMED_WORKSPACE_DEP, "--check-names"),
+    (BLOATED_WORKSPACE_FEATURES, "--check-features"),
+    (features::IMPLIED_DEPENDENCY_FEATURES, "--check-features"),
+    (features::DISABLED_DEFAULT_DEPENDENCY, "--check-features"),
+];
+/// Lints needing resolved dependencies, skipped with `--no-deps`.
+const RESOLVED_LINTS: &[&str] = &[
+    MISNAMED_WORKSPACE_DEP,
+    features::IMPLIED_DEPENDENCY_FEATURES,
+    features::DISABLED_DEFAULT_DEPENDENCY,
+];
+
+const USAGE: &str = "\
+Usage: cargo ab-lint [COMMAND] [OPTIONS]
+
+Commands:
+  check              Lint manifests (default)
+  fix                Apply fixes, same as --fix
+
+Options:
+  --fix              Apply fixes
+  --dry-run          With --fix, print fixes without writing them
+  --allow-dirty      With --fix, allow fixing manifests with uncommitted changes
+  --diff             With --fix, print the full diff of each fixed file
+  --patch-out <FILE> Write fixes to a patch file for `git apply` instead, implies --fix --dry-run
+  -v, --verbose      Print more details, includes --diff, --show-skipped & a note if the
+                     workspace isn't the git repository root
+  --show-skipped     Print members that weren't linted & why
+  --only <LINT>      Only report the lint id, enabling it if needed, e.g. --only whitespace
+                     implies --check-whitespace. --only unused-workspace-deps skips all other lints
+  --only-fixable     Without --fix, only report findings --fix resolves
+  --fail-fast        Exit on the first member with findings, can't be used with --fix
+  --format <FORMAT>  Output format: human (default), human-compact, yaml, count,
+                     gitlab-codequality
+  --paths-relative-to <cwd|workspace>
+                     Display manifest paths relative to the workspace root (default) or cwd
+  --print-config     Print the effective configuration & where each setting is from
+  --log-level <LEVEL>
+                     Internal logging: error, warn (default), info, debug, trace.
+                     RUST_LOG is also supported
+  --no-deps          Don't resolve dependencies, useful for workspaces that fail to resolve
+  --sandbox          For untrusted workspaces, implies --no-deps & only writes inside the workspace
+  --workspace        Lint all members, by default only default-members are linted if set
+  --relevant-to <PACKAGE>
+                     Only lint the member & members it depends on through path dependencies
+  --check-features   Lint dependency features using resolved metadata & workspace feature usage
+  --no-dev           Don't lint member dev-dependencies, --include-dev to override config
+  --check-names      Lint workspace dependency keys not matching the resolved crate name
+  --check-whitespace Lint trailing whitespace & final newlines
+  --check-toolchain  Report a workspace rust-version higher than the active rustc
+  --fix-unused-workspace-package
+                     With --fix, remove [workspace.package] fields no member inherits
+  --fix-dead-build-deps
+                     With --fix, remove [build-dependencies] of members without a build script
+  --migration-check  List optional dependencies relying on implicit features
+  --audit-deps       Report workspace dependencies & their versions in the dependency graph
+  --export-deps <FILE>
+                     Write a csv of workspace dependencies & the members using them";
 
 fn main() -> anyhow::Result<()> {
     if env::args().any(|a| a == "--help" || a == "-h") {
-        eprintln!("Usage: cargo ab-lint [--fix [--dry-run]]");
+        eprintln!("{USAGE}");
         return Ok(());
     }
 
-    let fix = env::args().any(|a| a == "--fix");
-    let dry_run = env::args().any(|a| a == "--dry-run");
+    let subcommand = subcommand();
+    match subcommand.as_deref() {
+        None | Some("check" | "fix") => {}
+        Some(other) => {
+            eprintln!("Unknown command `{other}`\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    }
+
+    init_logging()?;
+
+    let patch_out = arg_value("--patch-out");
+    let fix = subcommand.as_deref() == Some("fix")
+        || patch_out.is_some()
+        || env::args().any(|a| a == "--fix");
+    let dry_run = patch_out.is_some() || env::args().any(|a| a == "--dry-run");
+    let migration_check = flag_or_only("--migration-check");
+    let all_members = env::args().any(|a| a == "--workspace");
+    let check_whitespace = flag_or_only("--check-whitespace");
+    let check_toolchain = flag_or_only("--check-toolchain");
+    let remove_unused_ws_package = env::args().any(|a| a == "--fix-unused-workspace-package");
+    let remove_dead_build_deps = env::args().any(|a| a == "--fix-dead-build-deps");
+    let audit_deps = env::args().any(|a| a == "--audit-deps");
+    let allow_dirty = env::args().any(|a| a == "--allow-dirty");
+    let check_features = flag_or_only("--check-features");
+    let check_names = flag_or_only("--check-names");
+    let verbose = env::args().any(|a| a == "--verbose" || a == "-v");
+    let show_diff = verbose || env::args().any(|a| a == "--diff");
+    let show_skipped = verbose || env::args().any(|a| a == "--show-skipped");
+    let fail_fast = env::args().any(|a| a == "--fail-fast");
+    let only = arg_value("--only");
+    if let Some(only) = only.as_deref().filter(|only| !LINTS.contains(only)) {
+        anyhow::bail!(
+            "unknown lint `{only}` for --only, expected one of: {}",
+            LINTS.join(", ")
+        );
+    }
+    let unused_only = only.as_deref() == Some(UNUSED_WORKSPACE_DEPS);
+    if fix && only.is_some() && !unused_only {
+        anyhow::bail!("--only can't be used with --fix, except for --only {UNUSED_WORKSPACE_DEPS}");
+    }
+    if fail_fast && fix {
+        anyhow::bail!("--fail-fast can't be used with --fix");
+    }
+
+    // untrusted workspaces: don't resolve dependencies, which may run build scripts
+    let sandbox = env::args().any(|a| a == "--sandbox");
+    let no_deps = sandbox || env::args().any(|a| a == "--no-deps");
+    if let Some(only) = only
+        .as_deref()
+        .filter(|only| no_deps && RESOLVED_LINTS.contains(only))
+    {
+        anyhow::bail!("--only {only} needs resolved dependencies, it can't be used with --no-deps");
+    }
+
+    let mut metadata_cmd = cargo_metadata::MetadataCommand::new();
+    // --only unused-workspace-deps only reads the manifests
+    if no_deps || unused_only {
+        metadata_cmd.no_deps();
+    }
+    let meta = match metadata_cmd.exec() {
+        Ok(meta) => meta,
+        Err(err) => {
+            // report manifest problems more clearly than cargo, if possible
+            let cwd = Utf8PathBuf::from_path_buf(env::current_dir()?)
+                .map_err(|p| anyhow::anyhow!("non-utf8 current dir {}", p.display()))?;
+            if rejected::report(&cwd)? {
+                std::process::exit(1);
+            }
+            return Err(err).with_context(|| match no_deps {
+                true => "`cargo metadata --no-deps` failed, run it directly for details".to_owned(),
+                false => "Workspace doesn't resolve, run `cargo metadata` directly for details \
+                    or use `--no-deps` to lint without resolving dependencies"
+                    .to_owned(),
+            });
+        }
+    };
 
-    let meta = cargo_metadata::MetadataCommand::new().exec()?;
+    if fix && !dry_run && !allow_dirty {
+        let dirty = dirty_manifests(&meta.workspace_root);
+        if !dirty.is_empty() {
+            anyhow::bail!(
+                "uncommitted changes to manifests, commit them or use `--allow-dirty`:\n  {}",
+                dirty.join("\n  ")
+            );
+        }
+    }
 
     let root_toml = meta.workspace_root.join("Cargo.toml");
     let (root_manifest, mut root_doc, root_toml_str) = {
+        log::debug!("reading {root_toml}");
         let toml = fs::read_to_string(&root_toml)?;
+        let doc = toml.parse::<toml_edit::DocumentMut>()?;
         let manifest =
             Manifest::from_str(&toml).with_context(|| format!("{}", meta.workspace_root))?;
-        let doc = toml.parse::<toml_edit::DocumentMut>()?;
         (manifest, doc, toml)
     };
+    let config = config::Config::load(&root_doc).with_context(|| format!("{root_toml}"))?;
+    let cli_include_dev = cli_include_dev();
+    if env::args().any(|a| a == "--print-config") {
+        config.print(arg_value("--format").as_deref(), cli_include_dev);
+        return Ok(());
+    }
+    let include_dev = cli_include_dev.or(config.dev_dependencies).unwrap_or(true);
+    let disabled_setting = match only.as_deref() {
+        Some(DEPENDENCY_ORDER) => config
+            .dependency_order
+            .is_none()
+            .then_some("dependency-order"),
+        Some(UNINHERITED_MEMBER_DEP) => {
+            (config.inherit_member_deps != Some(true)).then_some("inherit-member-deps = true")
+        }
+        Some(UNPUBLISHED_DEFAULT_MEMBER) => (config.publishable_default_members != Some(true))
+            .then_some("publishable-default-members = true"),
+        _ => None,
+    };
+    if let (Some(only), Some(setting)) = (&only, disabled_setting) {
+        anyhow::bail!(
+            "--only {only} needs `{setting}` in [workspace.metadata.ab-lint] or the user config"
+        );
+    }
+    let format = output_format(&config)?;
+
+    if audit_deps {
+        return audit::audit_workspace_deps(&root_manifest, &meta);
+    }
+    if let Some(out) = arg_value("--export-deps") {
+        let mut members = vec![];
+        for package in meta.workspace_packages() {
+            let toml = fs::read_to_string(&package.manifest_path)?;
+            let manifest =
+                Manifest::from_str(&toml).with_context(|| format!("{}", package.manifest_path))?;
+            members.push(manifest);
+        }
+        let csv = audit::workspace_deps_csv(&root_manifest, &members);
+        log::debug!("writing {out}");
+        match sandbox {
+            true => {
+                let out = cargo_metadata::camino::Utf8Path::new(&out);
+                write_in_workspace(&meta.workspace_root, out, &csv)?
+            }
+            false => fs::write(out, csv)?,
+        }
+        return Ok(());
+    }
+
+    let workspace = WorkspaceDirs {
+        root: normalize_path(&meta.workspace_root),
+        members: meta
+            .workspace_members
+            .iter()
+            .filter_map(|m| m.manifest_path())
+            .filter_map(|p| Some(normalize_path(p.parent()?)))
+            .collect(),
+    };
 
-    let mut something_to_fix = false;
+    let mut reporter = Reporter::new(format);
+    reporter.only_fixable = !fix && env::args().any(|a| a == "--only-fixable");
+    reporter.only = only;
+    if verbose {
+        note_git_root(&reporter, &meta.workspace_root);
+    }
+    let mut patch = String::new();
     let mut member_manifests = vec![];
-    let cwd = env::current_dir().ok();
-    let cwd = cwd.as_ref();
+    // fixes to the root manifest are written once, after workspace lints
+    let mut root_fixes = vec![];
+    let mut root_linted_as_member = false;
+    let display_base = match arg_value("--paths-relative-to").as_deref() {
+        None | Some("workspace") => Some(meta.workspace_root.clone()),
+        Some("cwd") => env::current_dir()
+            .ok()
+            .and_then(|cwd| Utf8PathBuf::from_path_buf(cwd).ok()),
+        Some(other) => {
+            anyhow::bail!("unknown --paths-relative-to `{other}`, expected cwd|workspace")
+        }
+    };
+
+    // like cargo, without --workspace only lint default-members when set
+    let relevant_to = arg_value("--relevant-to");
+    let default_members = match (&relevant_to, &root_manifest.workspace) {
+        (Some(package), _) => Some(path_dependency_closure(&meta, package)?),
+        (None, Some(ws)) if !all_members && !ws.default_members.is_empty() => meta
+            .workspace_default_members
+            .is_available()
+            .then(|| meta.workspace_default_members.to_vec()),
+        _ => None,
+    };
+
+    let misnamed_ws_deps = match check_names {
+        true => misnamed_workspace_deps(&root_manifest, &meta),
+        false => vec![],
+    };
 
-    for member in meta.workspace_members {
+    let ws_info = WorkspaceInfo {
+        root: &root_manifest,
+        misnamed_deps: &misnamed_ws_deps,
+        dependency_order: config.dependency_order.as_deref(),
+        include_dev,
+        check_whitespace,
+    };
+
+    let not_linted_reason = match &relevant_to {
+        Some(package) => format!("not relevant to {package}"),
+        None => "not a default member".to_owned(),
+    };
+    // members not linted & the reason
+    let mut skipped = vec![];
+
+    for member in &meta.workspace_members {
         let Some(member_path) = member.manifest_path() else {
+            log::debug!("no manifest path for member {}", member.repr);
+            skipped.push((member.repr.clone(), "unresolved manifest path".to_owned()));
             continue;
         };
-        eprintln!(
-            "==> Checking {}",
-            cwd.and_then(|cwd| member_path.strip_prefix(cwd).ok())
-                .unwrap_or(&member_path)
-        );
 
-        let (member_manifest, mut member_doc, toml_str) = {
+        let display_path = display_base
+            .as_ref()
+            .and_then(|base| member_path.strip_prefix(base).ok())
+            .unwrap_or(&member_path);
+
+        if unused_only {
+            log::debug!("reading member deps {member_path}");
+            let toml = fs::read_to_string(&member_path)?;
+            let manifest = Manifest::from_str(&toml).with_context(|| format!("{member_path}"))?;
+            member_manifests.push(manifest);
+            continue;
+        }
+
+        if default_members
+            .as_ref()
+            .is_some_and(|defaults| !defaults.contains(member))
+        {
+            skipped.push((display_path.to_string(), not_linted_reason.clone()));
+            // still needed for workspace lints
+            log::debug!("reading non-default member {member_path}");
             let toml = fs::read_to_string(&member_path)?;
             let manifest = Manifest::from_str(&toml).with_context(|| format!("{member_path}"))?;
-            (manifest, toml.parse::<toml_edit::DocumentMut>()?, toml)
+            member_manifests.push(manifest);
+            continue;
+        }
+
+        reporter.checking(display_path);
+
+        log::debug!("reading {member_path}");
+        let toml_str = fs::read_to_string(&member_path)?;
+        let mut member_doc = toml_str.parse::<toml_edit::DocumentMut>()?;
+        let full_manifest =
+            Manifest::from_str(&toml_str).with_context(|| format!("{member_path}"))?;
+        // workspace lints always consider dev-dependencies
+        let member_manifest = match include_dev {
+            true => full_manifest.clone(),
+            false => without_dev_dependencies(&full_manifest),
+        };
+        let is_root = member_path == root_toml;
+        let doc = match is_root {
+            true => &mut root_doc,
+            false => &mut member_doc,
         };
-        let has_fixes = lint_manifest(&root_manifest, &member_manifest, &mut member_doc);
-        member_manifests.push(member_manifest);
-
-        if fix && has_fixes {
-            let fixed_toml = member_doc
-                .to_string()
-                .replace("workspace = true}", "workspace = true }")
-                .replace(" = { workspace = true }", ".workspace = true");
-            for diff in diff::lines(&toml_str, &fixed_toml) {
-                match diff {
-                    diff::Result::Left(old) => eprintln!("{}{}", "-".red(), old.red()),
-                    diff::Result::Right(new) => eprintln!("{}{}", "+".green(), new.green()),
-                    _ => {}
+        let mut findings = lint_member_doc(&toml_str, &member_manifest, doc, is_root, &ws_info);
+        if let Some(member_dir) = member_path.parent() {
+            build_deps_without_build_script(doc, member_dir, remove_dead_build_deps, &mut findings);
+        }
+        should_inherit_workspace_deps(&root_manifest, &member_manifest, &mut findings);
+        redundant_target_dependencies(&member_manifest, doc, &mut findings);
+        circular_features(&member_manifest, &mut findings);
+        renamed_dep_feature_refs(&member_manifest, &mut findings);
+        for table in dependency_tables(&member_manifest) {
+            unsatisfiable_version_reqs(&table, &mut findings);
+            if let Some(member_dir) = member_path.parent() {
+                path_deps_outside_workspace(&table, member_dir, &workspace, &mut findings);
+                inherited_self_dependencies(
+                    &table,
+                    &root_manifest,
+                    &meta,
+                    member_dir,
+                    &mut findings,
+                );
+                if config.inherit_member_deps == Some(true) {
+                    uninherited_member_deps(&table, member_dir, &meta, &mut findings);
                 }
             }
-            if !dry_run {
-                fs::write(&member_path, fixed_toml)?;
+        }
+        if migration_check {
+            implicit_optional_dependency_features(&member_manifest, &mut findings);
+        }
+        let dep_features = check_features
+            .then(|| features::DependencyFeatures::new(&meta, member))
+            .flatten();
+        if check_features && dep_features.is_none() {
+            log::debug!("{display_path}: no metadata, skipping feature lints");
+        }
+        for table in dependency_tables(&member_manifest) {
+            features::self_named_features(&table, dep_features.as_ref(), &mut findings);
+            if let Some(dep_features) = &dep_features {
+                features::implied_dependency_features(&table, dep_features, doc, &mut findings);
+                features::features_without_default_dependency(&table, dep_features, &mut findings);
+            }
+        }
+        member_manifests.push(full_manifest);
+
+        let fixes: Vec<_> = findings.iter().filter(|f| f.fixable).cloned().collect();
+        reporter.report(display_path, &toml_str, findings);
+
+        if fail_fast && reporter.has_issues() {
+            log::debug!("{display_path}: --fail-fast, skipping remaining members");
+            reporter.finish(fix);
+            std::process::exit(1);
+        }
+
+        if is_root {
+            log::debug!("{display_path}: root fixes deferred until after workspace lints");
+            root_linted_as_member = true;
+            root_fixes = fixes;
+            continue;
+        }
+
+        if fix && !fixes.is_empty() {
+            let fixed_toml = fixed_manifest(&toml_str, &member_doc, check_whitespace);
+            reporter.fix(
+                display_path,
+                &toml_str,
+                &fixed_toml,
+                &fixes,
+                dry_run,
+                show_diff,
+            );
+            if patch_out.is_some() {
+                patch += &patch::unified_diff(
+                    &workspace_relative(&meta.workspace_root, &member_path),
+                    &toml_str,
+                    &fixed_toml,
+                );
+            }
+            match dry_run {
+                true => log::debug!("{display_path}: dry run, not writing fixes"),
+                false => {
+                    log::debug!("{display_path}: writing fixes {:?}", fixed_lints(&fixes));
+                    write_in_workspace(&meta.workspace_root, &member_path, &fixed_toml)?;
+                }
             }
         }
-        something_to_fix |= has_fixes;
     }
 
-    if root_manifest.workspace.is_some() {
-        eprintln!(
-            "==> Checking workspace {}",
-            cwd.and_then(|cwd| root_toml.strip_prefix(cwd).ok())
-                .unwrap_or(&root_toml)
+    if show_skipped {
+        for (member, reason) in &skipped {
+            eprintln!(
+                "{} {member} {}",
+                "Skipped".dimmed(),
+                format!("({reason})").dimmed()
+            );
+        }
+    }
+
+    let root_display_path = display_base
+        .as_ref()
+        .and_then(|base| root_toml.strip_prefix(base).ok())
+        .unwrap_or(&root_toml);
+    match root_manifest.workspace.is_some() {
+        true => reporter.checking(format_args!("workspace {root_display_path}")),
+        false => reporter.note("No [workspace] table, running package-level lints only"),
+    }
+    let mut findings = vec![];
+    // --only unused-workspace-deps skips all other workspace lints
+    if !unused_only {
+        empty_default_members(&mut root_doc, &mut findings);
+        sourceless_workspace_deps(&root_doc, &mut findings);
+        member_patches(&root_manifest, &meta, &mut findings);
+        if config.publishable_default_members == Some(true) {
+            unpublished_default_members(&root_manifest, &meta, &mut findings);
+        }
+        let ws_deps_keys = ["workspace", "dependencies"].map(String::from);
+        padded_package_fields(&mut root_doc, &["workspace", "package"], &mut findings);
+        padded_dependency_versions(&mut root_doc, &ws_deps_keys, &mut findings);
+        redundant_optional_false(&mut root_doc, &ws_deps_keys, &mut findings);
+        empty_dependency_features(&mut root_doc, &ws_deps_keys, &mut findings);
+        for (key, name) in &misnamed_ws_deps {
+            findings.push(
+                Finding::new(
+                    MISNAMED_WORKSPACE_DEP,
+                    format!(
+                        "Workspace dependency {} should use the crate name {}",
+                        key.bold(),
+                        name.bold()
+                    ),
+                )
+                .fixable()
+                .at(["workspace", "dependencies", key]),
+            );
+            if let Some(deps) = root_doc["workspace"]["dependencies"].as_table_mut() {
+                rename_table_key(deps, key, name);
+            }
+        }
+        deprecated_license_slash(
+            &mut root_doc,
+            &["workspace", "package", "license"],
+            &mut findings,
         );
+        if check_toolchain {
+            rust_version_above_toolchain(&root_doc, &mut findings);
+        }
+        if let Some(prefixes) = &config.dependency_order {
+            let order = DependencyOrder { prefixes };
+            let keys = ["workspace", "dependencies"].map(String::from);
+            unordered_dependencies(&mut root_doc, &keys, &order, &mut findings);
+        }
+        if let Some(ws) = &root_manifest.workspace {
+            let table = DepTable {
+                deps: &ws.dependencies,
+                keys: vec!["workspace", "dependencies"],
+                item_name: "workspace dependency",
+            };
+            unsatisfiable_version_reqs(&table, &mut findings);
+            path_deps_outside_workspace(&table, &meta.workspace_root, &workspace, &mut findings);
+        }
     }
     let unused_ws_deps = unused_workspace_deps(&root_manifest, &member_manifests);
-    if !unused_ws_deps.is_empty() {
-        something_to_fix = true;
-        for dep in &unused_ws_deps {
-            eprintln!(
-                "{}",
-                format!("Unused workspace dependency {}", dep.bold()).yellow()
-            );
+    for dep in &unused_ws_deps {
+        findings.push(
+            Finding::new(
+                UNUSED_WORKSPACE_DEPS,
+                format!("Unused workspace dependency {}", dep.bold()),
+            )
+            .fixable()
+            .at(["workspace", "dependencies", dep]),
+        );
+    }
+    if fix && !unused_ws_deps.is_empty() {
+        let deps = root_doc["workspace"]["dependencies"]
+            .as_table_like_mut()
+            .unwrap();
+        for dep in unused_ws_deps {
+            deps.remove(dep);
+        }
+    }
+    if !unused_only {
+        let unused_ws_package = unused_workspace_package_fields(&root_doc, &member_manifests);
+        for field in &unused_ws_package {
+            let finding = Finding::new(
+                UNUSED_WORKSPACE_PACKAGE,
+                format!("Unused workspace package field {}", field.bold()),
+            )
+            .at(["workspace", "package", field]);
+            findings.push(match remove_unused_ws_package {
+                true => finding.fixable(),
+                false => finding,
+            });
         }
-        if fix {
-            let deps = root_doc["workspace"]["dependencies"]
+        if fix && remove_unused_ws_package && !unused_ws_package.is_empty() {
+            let package = root_doc["workspace"]["package"]
                 .as_table_like_mut()
                 .unwrap();
-            for dep in unused_ws_deps {
-                deps.remove(dep);
+            for field in unused_ws_package {
+                package.remove(&field);
             }
-            let fixed_toml = root_doc.to_string();
-            for diff in diff::lines(&root_toml_str, &fixed_toml) {
-                match diff {
-                    diff::Result::Left(old) => eprintln!("{}{}", "-".red(), old.red()),
-                    diff::Result::Right(new) => eprintln!("{}{}", "+".green(), new.green()),
-                    _ => {}
-                }
+        }
+        if check_whitespace && !root_linted_as_member {
+            whitespace_issues(&root_toml_str, &mut findings);
+        }
+        member_rust_versions(&root_manifest, &member_manifests, &mut findings);
+        for mixed in mixed_dependency_sources(&member_manifests) {
+            findings.push(Finding::new(
+                MIXED_SOURCES,
+                format!(
+                    "Dependency {} has mixed sources, path in {} & registry in {}",
+                    mixed.dep.bold(),
+                    format!("{:?}", mixed.path).bold(),
+                    format!("{:?}", mixed.registry).bold(),
+                ),
+            ));
+        }
+        if check_features {
+            bloated_workspace_features(&root_manifest, &member_manifests, &mut findings);
+        }
+        for divergent in divergent_version_reqs(&member_manifests) {
+            let reqs = divergent
+                .reqs
+                .iter()
+                .map(|(req, _, members)| format!("{} in {members:?}", format!("{req:?}").bold()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let suggestion = match version::common_req(divergent.reqs.iter().map(|(_, r, _)| r)) {
+                Some(req) => format!(
+                    ", e.g. workspace dependency {} = {}",
+                    divergent.dep,
+                    format!("{req:?}").bold()
+                ),
+                None => ", no version satisfies all".into(),
+            };
+            findings.push(
+                Finding::new(
+                    DIVERGENT_VERSIONS,
+                    format!(
+                        "Dependency {} has version requirements {reqs}{suggestion}",
+                        divergent.dep.bold()
+                    ),
+                )
+                .info()
+                .at(["workspace", "dependencies", divergent.dep]),
+            );
+        }
+    }
+
+    root_fixes.extend(findings.iter().filter(|f| f.fixable).cloned());
+    reporter.report(root_display_path, &root_toml_str, findings);
+
+    if fix && !root_fixes.is_empty() {
+        let fixed_toml = fixed_manifest(&root_toml_str, &root_doc, check_whitespace);
+        reporter.fix(
+            root_display_path,
+            &root_toml_str,
+            &fixed_toml,
+            &root_fixes,
+            dry_run,
+            show_diff,
+        );
+        if patch_out.is_some() {
+            patch += &patch::unified_diff("Cargo.toml", &root_toml_str, &fixed_toml);
+        }
+        match dry_run {
+            true => log::debug!("{root_display_path}: dry run, not writing fixes"),
+            false => {
+                log::debug!(
+                    "{root_display_path}: writing fixes {:?}",
+                    fixed_lints(&root_fixes)
+                );
+                write_in_workspace(&meta.workspace_root, &root_toml, &fixed_toml)?;
             }
-            if !dry_run {
-                fs::write(&root_toml, fixed_toml)?;
+        }
+    }
+
+    if let Some(out) = &patch_out {
+        log::debug!("writing patch {out}");
+        match sandbox {
+            true => {
+                let out = cargo_metadata::camino::Utf8Path::new(out);
+                write_in_workspace(&meta.workspace_root, out, &patch)?
             }
+            false => fs::write(out, &patch)?,
         }
     }
 
-    if !fix && something_to_fix {
-        eprintln!(
-            "{}{}",
-            "Hint: To fix run with ".dimmed(),
-            "--fix".dimmed().bold()
-        );
+    if reporter.finish(fix) {
         std::process::exit(1);
     }
+    Ok(())
+}
+
+/// Workspace info collected up front that member manifest lints need.
+struct WorkspaceInfo<'a> {
+    root: &'a Manifest,
+    /// Misnamed `[workspace.dependencies]` keys & the crate name, see [`misnamed_workspace_deps`].
+    misnamed_deps: &'a [(String, String)],
+    dependency_order: Option<&'a [String]>,
+    include_dev: bool,
+    check_whitespace: bool,
+}
+
+/// Lints of the member manifest `toml` needing only its contents & the workspace info,
+/// applying fixes to `doc`.
+///
+/// `member` excludes dev-dependencies unless `ws.include_dev`.
+fn lint_member_doc(
+    toml: &str,
+    member: &Manifest,
+    doc: &mut toml_edit::DocumentMut,
+    is_root: bool,
+    ws: &WorkspaceInfo<'_>,
+) -> Vec<Finding> {
+    let linted_table_keys = |doc: &toml_edit::DocumentMut| {
+        package_dependency_table_keys(doc)
+            .into_iter()
+            .filter(|k| ws.include_dev || k.last().is_some_and(|t| t != "dev-dependencies"))
+            .collect::<Vec<_>>()
+    };
+
+    let mut findings = lint_manifest(ws.root, member, doc);
+    if ws.check_whitespace {
+        whitespace_issues(toml, &mut findings);
+    }
+    redundant_auto_discovery(doc, &mut findings);
+    default_documentation_url(doc, &mut findings);
+    deprecated_license_slash(doc, &["package", "license"], &mut findings);
+    if !is_root {
+        ineffective_resolver(doc, &mut findings);
+    }
+    ignored_profiles(doc, is_root, &mut findings);
+    padded_package_fields(doc, &["package"], &mut findings);
+    for keys in linted_table_keys(doc) {
+        padded_dependency_versions(doc, &keys, &mut findings);
+        redundant_optional_false(doc, &keys, &mut findings);
+        empty_dependency_features(doc, &keys, &mut findings);
+    }
+    for (key, name) in ws.misnamed_deps {
+        misnamed_inherited_deps(doc, key, name, &mut findings);
+    }
+    if let Some(prefixes) = ws.dependency_order {
+        let order = DependencyOrder { prefixes };
+        for keys in linted_table_keys(doc) {
+            unordered_dependencies(doc, &keys, &order, &mut findings);
+        }
+    }
+    findings
+}
+
+/// The fixed `doc` formatted like the `original` toml.
+fn fixed_manifest(original: &str, doc: &toml_edit::DocumentMut, trim_whitespace: bool) -> String {
+    let fixed = TomlStyle::detect(original).format_fixed(original, &doc.to_string());
+    match trim_whitespace {
+        true => style::trim_whitespace(&fixed),
+        false => fixed,
+    }
+}
+
+/// Apply fixes to the in-memory member manifests keyed by path, returning the fixed
+/// manifests that changed.
+///
+/// Doesn't touch disk or run `cargo metadata`, so only [`lint_member_doc`] fixes apply.
+#[cfg(test)]
+fn fix_manifests<K: Ord + Clone>(
+    manifests: &std::collections::BTreeMap<K, String>,
+    ws: &WorkspaceInfo<'_>,
+) -> anyhow::Result<std::collections::BTreeMap<K, String>> {
+    let mut fixed = std::collections::BTreeMap::new();
+    for (path, toml) in manifests {
+        let mut doc = toml.parse::<toml_edit::DocumentMut>()?;
+        let mut member = Manifest::from_str(toml)?;
+        if !ws.include_dev {
+            member = without_dev_dependencies(&member);
+        }
+        let findings = lint_member_doc(toml, &member, &mut doc, false, ws);
+        if findings.iter().any(|f| f.fixable) {
+            let fixed_toml = fixed_manifest(toml, &doc, ws.check_whitespace);
+            if fixed_toml != *toml {
+                fixed.insert(path.clone(), fixed_toml);
+            }
+        }
+    }
+    Ok(fixed)
+}
+
+/// Whether the `flag` is passed, or implied by `--only` naming a lint it enables.
+fn flag_or_only(flag: &str) -> bool {
+    env::args().any(|a| a == flag)
+        || arg_value("--only").is_some_and(|only| FLAG_LINTS.contains(&(only.as_str(), flag)))
+}
 
-    eprintln!("{}", "All good ✔".green());
+/// `--include-dev` or `--no-dev`, overriding the `dev-dependencies` config.
+fn cli_include_dev() -> Option<bool> {
+    env::args()
+        .find(|a| a == "--no-dev" || a == "--include-dev")
+        .map(|a| a == "--include-dev")
+}
+
+/// `--format`, otherwise the configured `format`.
+fn output_format(config: &config::Config) -> anyhow::Result<Format> {
+    match arg_value("--format").or(config.format.clone()) {
+        Some(f) => f.parse(),
+        None => Ok(Format::Human),
+    }
+}
 
+/// Logs to stderr, filtered by `--log-level` or `RUST_LOG`.
+fn init_logging() -> anyhow::Result<()> {
+    let mut logger =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"));
+    if let Some(level) = arg_value("--log-level") {
+        let level: log::LevelFilter = level
+            .parse()
+            .with_context(|| format!("invalid --log-level `{level}`"))?;
+        logger.filter_level(level);
+    }
+    logger.init();
     Ok(())
 }
 
-fn unused_workspace_deps<'a>(root: &'a Manifest, members: &[Manifest]) -> Vec<&'a str> {
-    root.workspace
-        .iter()
-        .flat_map(|w| w.dependencies.keys())
-        .filter(|dep| {
-            !members.iter().any(|m| {
-                m.dependencies.contains_key(*dep)
-                    || m.dev_dependencies.contains_key(*dep)
-                    || m.build_dependencies.contains_key(*dep)
-            })
-        })
-        .map(|dep| dep.as_str())
-        .collect()
+/// Write `contents` to `path`, erroring if it's outside the workspace `root`.
+fn write_in_workspace(
+    root: &cargo_metadata::camino::Utf8Path,
+    path: &cargo_metadata::camino::Utf8Path,
+    contents: &str,
+) -> anyhow::Result<()> {
+    let cwd = env::current_dir()?;
+    let cwd = Utf8PathBuf::from_path_buf(cwd).map_err(|_| anyhow::anyhow!("non-utf8 cwd"))?;
+    let target = normalize_path(&cwd.join(path));
+    // resolve symlinks, e.g. a symlinked member dir, the file itself may not exist yet
+    let resolved = match target.canonicalize_utf8() {
+        Ok(resolved) => resolved,
+        Err(_) => {
+            let (Some(dir), Some(name)) = (target.parent(), target.file_name()) else {
+                anyhow::bail!("refusing to write {path}, not a file path");
+            };
+            dir.canonicalize_utf8()
+                .with_context(|| format!("{dir}"))?
+                .join(name)
+        }
+    };
+    anyhow::ensure!(
+        resolved.starts_with(root.canonicalize_utf8()?),
+        "refusing to write {path} outside the workspace root {root}"
+    );
+    fs::write(path, contents)?;
+    Ok(())
 }
 
-fn lint_manifest(root: &Manifest, member: &Manifest, doc: &mut toml_edit::DocumentMut) -> bool {
-    let mut has_fixes = false;
+/// `/`-separated `path` relative to the workspace `root`.
+fn workspace_relative(root: &cargo_metadata::camino::Utf8Path, path: &Utf8PathBuf) -> String {
+    let path = path.strip_prefix(root).unwrap_or(path);
+    path.as_str().replace('\\', "/")
+}
 
-    for (name, ws_dep) in root.workspace.iter().flat_map(|ws| &ws.dependencies) {
-        if let Some(cargo_toml::Dependency::Inherited(dep)) = member.dependencies.get(name) {
-            if dep.workspace {
-                let doc_deps = &mut doc["dependencies"];
-
-                has_fixes |= dependency_with_redundant_workspace_features(
-                    name,
-                    ws_dep,
-                    dep,
-                    doc_deps,
-                    "dependency",
-                );
-                has_fixes |=
-                    workspace_dependency_with_default_features_set(name, doc_deps, "dependency");
+/// Leading non-option arg, e.g. "fix" in `cargo ab-lint fix`.
+fn subcommand() -> Option<String> {
+    // cargo invokes `cargo-ab-lint ab-lint [ARGS]`
+    let mut args = env::args().skip(1).peekable();
+    args.next_if(|a| a == "ab-lint");
+    args.next().filter(|a| !a.starts_with('-'))
+}
+
+/// Member `package` & the members it depends on through path dependencies, transitively.
+fn path_dependency_closure(
+    meta: &cargo_metadata::Metadata,
+    package: &str,
+) -> anyhow::Result<Vec<PackageId>> {
+    let members = meta.workspace_packages();
+    let root = members
+        .iter()
+        .find(|p| p.name == package)
+        .with_context(|| format!("--relevant-to `{package}` is not a workspace member"))?;
+
+    let mut closure = vec![root.id.clone()];
+    let mut idx = 0;
+    while let Some(id) = closure.get(idx) {
+        let package = members.iter().find(|p| p.id == *id).unwrap();
+        let local_deps: Vec<_> = package
+            .dependencies
+            .iter()
+            .filter_map(|d| d.path.as_ref())
+            .filter_map(|dir| {
+                members
+                    .iter()
+                    .find(|m| m.manifest_path.parent() == Some(dir.as_path()))
+            })
+            .map(|m| m.id.clone())
+            .collect();
+        for dep in local_deps {
+            if !closure.contains(&dep) {
+                closure.push(dep);
             }
         }
-        if let Some(cargo_toml::Dependency::Inherited(dep)) = member.dev_dependencies.get(name) {
-            if dep.workspace {
-                let doc_devdeps = &mut doc["dev-dependencies"];
+        idx += 1;
+    }
+    log::debug!("--relevant-to {package}: {} members", closure.len());
+    Ok(closure)
+}
 
-                has_fixes |= dependency_with_redundant_workspace_features(
-                    name,
-                    ws_dep,
-                    dep,
-                    doc_devdeps,
-                    "dev-dependency",
-                );
-                has_fixes |= workspace_dependency_with_default_features_set(
-                    name,
-                    doc_devdeps,
-                    "dev-dependency",
-                );
-            }
+/// Value of `--name value` or `--name=value` arg.
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = env::args();
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(name).and_then(|a| a.strip_prefix('=')) {
+            return Some(value.to_owned());
         }
     }
+    None
+}
 
-    has_fixes
+/// Item at the toml key path, e.g. `["dependencies", "serde"]`.
+fn doc_item_mut<'d>(
+    doc: &'d mut toml_edit::DocumentMut,
+    keys: &[&str],
+) -> Option<&'d mut toml_edit::Item> {
+    let mut item = doc.as_item_mut();
+    for key in keys {
+        // not `Item::get_mut`, which inserts missing keys
+        item = item.as_table_like_mut()?.get_mut(key)?;
+    }
+    Some(item)
 }
 
-/// workspace=true dependencies setting default-features has no effect.
-fn workspace_dependency_with_default_features_set(
-    dep_name: &str,
-    doc_deps: &mut toml_edit::Item,
-    item_name: &str,
-) -> bool {
-    if let Some(table) = doc_deps[dep_name].as_table_like_mut() {
-        let fixes = table.remove("default-features").is_some()
-            || table.remove("default_features").is_some();
+/// `Cargo.toml` files with uncommitted git changes.
+///
+/// Empty if not a git repo or git isn't available.
+fn dirty_manifests(dir: &cargo_metadata::camino::Utf8Path) -> Vec<String> {
+    let Some(status) = git_output(dir, &["status", "--porcelain", "--", "*Cargo.toml"]) else {
+        return vec![];
+    };
+    status
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(|path| path.to_owned())
+        .collect()
+}
 
-        if fixes {
-            eprintln!(
-                "{}",
-                format!(
-                    "Redundant default-features set in workspace {item_name} {}",
-                    dep_name.bold()
-                )
-                .yellow()
-            );
-        }
-        return fixes;
+/// Note if the workspace isn't at the root of its git repository, listing other workspaces
+/// in the repository. Nothing if not in a git repository.
+fn note_git_root(reporter: &Reporter, workspace_root: &cargo_metadata::camino::Utf8Path) {
+    let Some(git_root) = git_output(workspace_root, &["rev-parse", "--show-toplevel"]) else {
+        return;
+    };
+    let git_root = Utf8PathBuf::from(git_root.trim());
+    let Ok(workspace_dir) = workspace_root.canonicalize() else {
+        return;
+    };
+    if same_dir(git_root.as_std_path(), &workspace_dir) {
+        return;
+    }
+    reporter.note(&format!(
+        "Linting workspace {workspace_root}, not the git repository root {git_root}"
+    ));
+
+    let Some(manifests) = git_output(&git_root, &["ls-files", "--", "*Cargo.toml"]) else {
+        return;
+    };
+    let other_workspaces: Vec<_> = manifests
+        .lines()
+        .map(|path| git_root.join(path))
+        .filter(|path| {
+            path.parent()
+                .is_some_and(|dir| !same_dir(dir.as_std_path(), &workspace_dir))
+        })
+        .filter(|path| {
+            fs::read_to_string(path)
+                .ok()
+                .and_then(|toml| toml.parse::<toml_edit::DocumentMut>().ok())
+                .is_some_and(|doc| doc.contains_key("workspace"))
+        })
+        .filter_map(|path| path.parent().map(|dir| dir.to_string()))
+        .collect();
+    if !other_workspaces.is_empty() {
+        reporter.note(&format!(
+            "Other workspaces in the repository, run in their directory to lint them: {}",
+            other_workspaces.join(", ")
+        ));
     }
-    false
 }
 
-/// workspace=true dependencies do not need to restate the workspace features.
-fn dependency_with_redundant_workspace_features(
-    dep_name: &str,
-    ws_dep: &cargo_toml::Dependency,
-    dep: &cargo_toml::InheritedDependencyDetail,
-    doc_deps: &mut toml_edit::Item,
-    item_name: &str,
-) -> bool {
-    let mut has_fixes = false;
+/// Whether `a` resolves to the canonical directory `b`, following symlinks.
+fn same_dir(a: &std::path::Path, b: &std::path::Path) -> bool {
+    a.canonicalize().is_ok_and(|a| a == b)
+}
 
-    let redundant_features: Vec<_> = dep
-        .features
+/// Stdout of a successful git command run in `dir`.
+fn git_output(dir: &cargo_metadata::camino::Utf8Path, args: &[&str]) -> Option<String> {
+    let out = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// `[workspace.package]` fields that no member inherits.
+fn unused_workspace_package_fields(
+    root_doc: &toml_edit::DocumentMut,
+    members: &[Manifest],
+) -> Vec<String> {
+    let Some(ws_package) = root_doc
+        .get("workspace")
+        .and_then(|ws| ws.get("package"))
+        .and_then(|p| p.as_table_like())
+    else {
+        return vec![];
+    };
+
+    let inherited: Vec<_> = members
         .iter()
-        .filter(|f| ws_dep.req_features().contains(f))
-        .map(|s| s.as_str())
+        .filter_map(|m| m.package.as_ref())
+        .flat_map(inherited_package_fields)
+        .collect();
+
+    ws_package
+        .iter()
+        .map(|(field, _)| field)
+        .filter(|field| !inherited.contains(&field.replace('_', "-").as_str()))
+        .map(|field| field.to_owned())
+        .collect()
+}
+
+/// Members setting their own `rust-version` in a workspace with `[workspace.package]`
+/// but no workspace `rust-version` to inherit.
+fn member_rust_versions(root: &Manifest, members: &[Manifest], findings: &mut Vec<Finding>) {
+    let Some(ws_package) = root.workspace.as_ref().and_then(|ws| ws.package.as_ref()) else {
+        return;
+    };
+    if ws_package.rust_version.is_some() {
+        return;
+    }
+
+    let setting: Vec<_> = members
+        .iter()
+        .filter_map(|m| m.package.as_ref())
+        .filter(|p| p.rust_version.as_ref().is_some_and(|v| v.is_set()))
+        .map(|p| p.name())
         .collect();
+    if setting.is_empty() {
+        return;
+    }
 
-    if !redundant_features.is_empty() {
-        eprintln!(
-            "{}",
+    findings.push(
+        Finding::new(
+            MEMBER_RUST_VERSION,
             format!(
-                "Redundant feature(s) {} for workspace {item_name} {}",
-                format!("{redundant_features:?}").bold(),
-                dep_name.bold(),
-            )
-            .yellow()
-        );
-        has_fixes = true;
+                "Members {} set rust-version but {} has none, \
+                 consider defining it there & inheriting it with rust-version.workspace = true",
+                format!("{setting:?}").bold(),
+                "[workspace.package]".bold(),
+            ),
+        )
+        .info()
+        .at(["workspace", "package"]),
+    );
+}
 
-        let feats = doc_deps[dep_name]["features"].as_array_mut().unwrap();
-        let rm_idx: Vec<_> = feats
-            .iter()
-            .enumerate()
-            .filter(|(_, v)| v.as_str().is_some_and(|s| redundant_features.contains(&s)))
-            .map(|(idx, _)| idx)
-            .collect();
-        for idx in rm_idx.into_iter().rev() {
-            feats.remove(idx);
-        }
+/// `[package]` fields set with `field.workspace = true`.
+fn inherited_package_fields(p: &cargo_toml::Package) -> Vec<&'static str> {
+    fn inherited<T>(field: &Option<cargo_toml::Inheritable<T>>) -> bool {
+        field.as_ref().is_some_and(|f| !f.is_set())
+    }
 
-        if feats.is_empty() {
-            doc_deps[dep_name]
-                .as_table_like_mut()
-                .unwrap()
-                .remove("features");
-        }
+    [
+        ("version", !p.version.is_set()),
+        ("edition", !p.edition.is_set()),
+        ("rust-version", inherited(&p.rust_version)),
+        ("authors", !p.authors.is_set()),
+        ("description", inherited(&p.description)),
+        ("homepage", inherited(&p.homepage)),
+        ("documentation", inherited(&p.documentation)),
+        ("readme", !p.readme.is_set()),
+        ("keywords", !p.keywords.is_set()),
+        ("categories", !p.categories.is_set()),
+        ("exclude", !p.exclude.is_set()),
+        ("include", !p.include.is_set()),
+        ("license", inherited(&p.license)),
+        ("license-file", inherited(&p.license_file)),
+        ("repository", inherited(&p.repository)),
+        ("publish", !p.publish.is_set()),
+    ]
+    .into_iter()
+    .filter(|(_, inherited)| *inherited)
+    .map(|(field, _)| field)
+    .collect()
+}
+
+/// Trailing whitespace on lines & missing/extra final newlines.
+fn whitespace_issues(toml: &str, findings: &mut Vec<Finding>) {
+    for line in style::trailing_whitespace_lines(toml) {
+        findings.push(
+            Finding::new(WHITESPACE, "Trailing whitespace")
+                .fixable()
+                .line(line),
+        );
     }
 
-    has_fixes
+    let content = toml.trim_end_matches(['\r', '\n']);
+    let eol = &toml[content.len()..];
+    let final_newline_ok =
+        toml.is_empty() || (!content.is_empty() && (eol == "\n" || eol == "\r\n"));
+    if !final_newline_ok {
+        findings.push(
+            Finding::new(WHITESPACE, "Manifest should end with exactly one newline")
+                .fixable()
+                .line(content.lines().count().max(1)),
+        );
+    }
 }
 
-trait PackageIdExt {
-    fn manifest_path(&self) -> Option<Utf8PathBuf>;
+fn unused_workspace_deps<'a>(root: &'a Manifest, members: &[Manifest]) -> Vec<&'a str> {
+    root.workspace
+        .iter()
+        .flat_map(|w| w.dependencies.keys())
+        .filter(|dep| {
+            !members
+                .iter()
+                .any(|m| dependency_tables(m).any(|t| t.deps.contains_key(*dep)))
+        })
+        .map(|dep| dep.as_str())
+        .collect()
 }
-impl PackageIdExt for PackageId {
+
+/// `[package]` auto target discovery keys default to true, so setting them to true is redundant.
+fn redundant_auto_discovery(doc: &mut toml_edit::DocumentMut, findings: &mut Vec<Finding>) {
+    let Some(package) = doc.get_mut("package").and_then(|p| p.as_table_like_mut()) else {
+        return;
+    };
+    for key in [
+        "autolib",
+        "autobins",
+        "autoexamples",
+        "autotests",
+        "autobenches",
+    ] {
+        if package.get(key).and_then(|v| v.as_bool()) == Some(true) {
+            findings.push(
+                Finding::new(
+                    REDUNDANT_AUTO_DISCOVERY,
+                    format!("Redundant {} (default)", format!("{key} = true").bold()),
+                )
+                .fixable()
+                .at(["package", key]),
+            );
+            package.remove(key);
+        }
+    }
+}
+
+/// `[workspace] default-members = []` makes a bare `cargo build` build nothing,
+/// removing it restores the default of all members.
+fn empty_default_members(doc: &mut toml_edit::DocumentMut, findings: &mut Vec<Finding>) {
+    let Some(ws) = doc.get_mut("workspace").and_then(|w| w.as_table_like_mut()) else {
+        return;
+    };
+    let empty = ws
+        .get("default-members")
+        .and_then(|m| m.as_array())
+        .is_some_and(|m| m.is_empty());
+    if empty {
+        findings.push(
+            Finding::new(
+                EMPTY_DEFAULT_MEMBERS,
+                format!(
+                    "Empty {} builds nothing by default, likely unintended",
+                    "default-members".bold()
+                ),
+            )
+            .fixable()
+            .at(["workspace", "default-members"]),
+        );
+        ws.remove("default-members");
+    }
+}
+
+/// `[workspace.package] rust-version` higher than the active rustc, which can't build at that MSRV.
+fn rust_version_above_toolchain(doc: &toml_edit::DocumentMut, findings: &mut Vec<Finding>) {
+    let Some(declared) = doc
+        .get("workspace")
+        .and_then(|ws| ws.get("package"))
+        .and_then(|p| p.get("rust-version"))
+        .and_then(|v| v.as_str())
+    else {
+        return;
+    };
+    let Some(declared_v) = version::parse_rust_version(declared) else {
+        log::debug!("unrecognised rust-version `{declared}`");
+        return;
+    };
+    let Some(rustc) = rustc_version() else {
+        log::warn!("couldn't determine the rustc version, skipping --check-toolchain");
+        return;
+    };
+    let Some(rustc_v) = version::parse_rust_version(&rustc) else {
+        log::debug!("unrecognised rustc version `{rustc}`");
+        return;
+    };
+    if declared_v > rustc_v {
+        findings.push(
+            Finding::new(
+                MSRV_ABOVE_TOOLCHAIN,
+                format!(
+                    "Workspace {} is higher than the active rustc {}",
+                    format!("rust-version = {declared:?}").bold(),
+                    rustc.bold()
+                ),
+            )
+            .info()
+            .at(["workspace", "package", "rust-version"]),
+        );
+    }
+}
+
+/// Version of the active rustc, using `RUSTC` if set, e.g. "1.80.1".
+fn rustc_version() -> Option<String> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let out = std::process::Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    // e.g. "rustc 1.80.1 (3f5fd8dd4 2024-08-06)"
+    String::from_utf8_lossy(&out.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(|v| v.to_owned())
+}
+
+/// `[workspace.dependencies]` without a `version`, `git` or `path`, e.g. `foo = {}`.
+///
+/// Checks the toml document as such entries can't be inherited by members.
+fn sourceless_workspace_deps(doc: &toml_edit::DocumentMut, findings: &mut Vec<Finding>) {
+    let Some(deps) = doc
+        .get("workspace")
+        .and_then(|ws| ws.get("dependencies"))
+        .and_then(|d| d.as_table_like())
+    else {
+        return;
+    };
+    for (name, dep) in deps.iter() {
+        let Some(dep) = dep.as_table_like() else {
+            continue;
+        };
+        if !["version", "git", "path"]
+            .iter()
+            .any(|k| dep.contains_key(k))
+        {
+            findings.push(
+                Finding::new(
+                    SOURCELESS_WORKSPACE_DEP,
+                    format!(
+                        "Workspace dependency {} has no {}, {} or {}",
+                        name.bold(),
+                        "version".bold(),
+                        "git".bold(),
+                        "path".bold(),
+                    ),
+                )
+                .error()
+                .at(["workspace", "dependencies", name]),
+            );
+        }
+    }
+}
+
+/// Workspace dependency keys, without `package`, differing in casing or `-`/`_` from
+/// the resolved crate name, e.g. `("Serde", "serde")`.
+///
+/// Empty if dependencies aren't resolved, e.g. with `--no-deps`.
+fn misnamed_workspace_deps(
+    root: &Manifest,
+    meta: &cargo_metadata::Metadata,
+) -> Vec<(String, String)> {
+    let normalize = |name: &str| name.to_ascii_lowercase().replace('-', "_");
+    let (Some(ws), Some(resolve)) = (&root.workspace, &meta.resolve) else {
+        return vec![];
+    };
+    let names: std::collections::HashMap<_, _> = meta
+        .packages
+        .iter()
+        .map(|p| (&p.id, p.name.as_str()))
+        .collect();
+    // member dependencies & the package each resolves to
+    let member_deps: Vec<_> = resolve
+        .nodes
+        .iter()
+        .filter(|node| meta.workspace_members.contains(&node.id))
+        .flat_map(|node| &node.deps)
+        .filter_map(|dep| Some((normalize(&dep.name), *names.get(&dep.pkg)?)))
+        .collect();
+
+    ws.dependencies
+        .iter()
+        .filter(|(_, dep)| dep.package().is_none())
+        .filter_map(|(key, _)| {
+            let (_, name) = member_deps
+                .iter()
+                .find(|(dep, name)| *dep == normalize(key) && *name != key)?;
+            Some((key.clone(), name.to_string()))
+        })
+        .collect()
+}
+
+/// Member dependencies inheriting the misnamed workspace dependency `key`, renamed to `name`,
+/// along with their `[features]` references.
+fn misnamed_inherited_deps(
+    doc: &mut toml_edit::DocumentMut,
+    key: &str,
+    name: &str,
+    findings: &mut Vec<Finding>,
+) {
+    let mut renamed = false;
+    for keys in package_dependency_table_keys(doc) {
+        let item_keys: Vec<_> = keys.iter().map(|k| k.as_str()).collect();
+        let Some(table) = doc_item_mut(doc, &item_keys).and_then(|t| t.as_table_mut()) else {
+            continue;
+        };
+        let inherits = table
+            .get(key)
+            .and_then(|d| d.get("workspace"))
+            .and_then(|w| w.as_bool())
+            == Some(true);
+        if !inherits {
+            continue;
+        }
+        findings.push(
+            Finding::new(
+                MISNAMED_WORKSPACE_DEP,
+                format!(
+                    "Dependency {} should use the crate name {}",
+                    key.bold(),
+                    name.bold()
+                ),
+            )
+            .fixable()
+            .at(keys.iter().map(|k| k.as_str()).chain([key])),
+        );
+        rename_table_key(table, key, name);
+        renamed = true;
+    }
+    if renamed {
+        rename_feature_refs(doc, key, name);
+    }
+}
+
+/// Rewrite `[features]` references to the dependency `from`, i.e. `dep:from`, `from/feat`,
+/// `from?/feat` & the implicit feature `from`, to `to`.
+fn rename_feature_refs(doc: &mut toml_edit::DocumentMut, from: &str, to: &str) {
+    let Some(features) = doc.get_mut("features").and_then(|f| f.as_table_like_mut()) else {
+        return;
+    };
+    // a feature named `from` isn't the implicit dependency feature
+    let implicit = !features.contains_key(from);
+    for (_, values) in features.iter_mut() {
+        let Some(values) = values.as_array_mut() else {
+            continue;
+        };
+        for value in values.iter_mut() {
+            let Some(v) = value.as_str() else {
+                continue;
+            };
+            let renamed = if v == format!("dep:{from}") {
+                format!("dep:{to}")
+            } else if v == from && implicit {
+                to.to_owned()
+            } else if let Some(feat) = v.strip_prefix(from).filter(|f| f.starts_with(['/', '?'])) {
+                format!("{to}{feat}")
+            } else {
+                continue;
+            };
+            let decor = value.decor().clone();
+            *value = renamed.into();
+            *value.decor_mut() = decor;
+        }
+    }
+}
+
+/// Rename the `from` entry to `to`, keeping the entry order & formatting.
+fn rename_table_key(table: &mut toml_edit::Table, from: &str, to: &str) {
+    let keys: Vec<_> = table.iter().map(|(k, _)| k.to_owned()).collect();
+    for key in keys {
+        let Some((mut k, item)) = table.remove_entry(&key) else {
+            continue;
+        };
+        if key == from {
+            k = toml_edit::Key::new(to).with_leaf_decor(k.leaf_decor().clone());
+        }
+        table.insert_formatted(&k, item);
+    }
+}
+
+/// Workspace dependency features beyond those inheriting members reference themselves,
+/// i.e. in their dependency `features` or `[features]` `dep/feature` values.
+///
+/// Informational, narrowing the workspace features may break members relying on them.
+fn bloated_workspace_features(root: &Manifest, members: &[Manifest], findings: &mut Vec<Finding>) {
+    let Some(ws) = &root.workspace else {
+        return;
+    };
+    for (name, ws_dep) in &ws.dependencies {
+        let ws_features = ws_dep.req_features();
+        if ws_features.is_empty() {
+            continue;
+        }
+
+        let mut used: Vec<&str> = vec![];
+        let mut inherited = false;
+        for member in members {
+            let mut member_inherits = false;
+            for table in dependency_tables(member) {
+                let Some(cargo_toml::Dependency::Inherited(dep)) = table.deps.get(name) else {
+                    continue;
+                };
+                if dep.workspace {
+                    member_inherits = true;
+                    used.extend(dep.features.iter().map(|f| f.as_str()));
+                }
+            }
+            if !member_inherits {
+                continue;
+            }
+            inherited = true;
+            let feature_refs = member.features.values().flatten().filter_map(|f| {
+                let (dep, feature) = f.split_once('/')?;
+                (dep.trim_end_matches('?') == name).then_some(feature)
+            });
+            used.extend(feature_refs);
+        }
+        used.sort_unstable();
+        used.dedup();
+
+        if !inherited || used.is_empty() {
+            continue;
+        }
+        if ws_features.iter().all(|f| used.contains(&f.as_str())) {
+            continue;
+        }
+        findings.push(
+            Finding::new(
+                BLOATED_WORKSPACE_FEATURES,
+                format!(
+                    "Workspace dependency {} features {} exceed the {} members use, \
+                    consider narrowing",
+                    name.bold(),
+                    format!("{ws_features:?}").bold(),
+                    format!("{used:?}").bold(),
+                ),
+            )
+            .info()
+            .at(["workspace", "dependencies", name]),
+        );
+    }
+}
+
+/// Dependency tables written as an array of tables, e.g. `[[dependencies]]`,
+/// which cargo can't parse.
+fn dependency_arrays_of_tables(doc: &toml_edit::DocumentMut) -> Vec<Finding> {
+    let mut keys = package_dependency_table_keys(doc);
+    keys.push(vec!["workspace".into(), "dependencies".into()]);
+    keys.into_iter()
+        .filter(|keys| {
+            keys.iter()
+                .try_fold(doc.as_item(), |item, k| item.get(k))
+                .is_some_and(|t| t.is_array_of_tables())
+        })
+        .map(|keys| {
+            let table = keys.join(".");
+            Finding::new(
+                DEPENDENCY_ARRAY_OF_TABLES,
+                format!(
+                    "{} must be a table, not an array of tables {}",
+                    table.bold(),
+                    format!("[[{table}]]").bold()
+                ),
+            )
+            .at(keys)
+        })
+        .collect()
+}
+
+/// Copy of the manifest without `[dev-dependencies]`, including `[target.*]` tables.
+fn without_dev_dependencies(manifest: &Manifest) -> Manifest {
+    let mut manifest = manifest.clone();
+    manifest.dev_dependencies.clear();
+    for target in manifest.target.values_mut() {
+        target.dev_dependencies.clear();
+    }
+    manifest
+}
+
+/// Key paths of the package dependency tables, including `[target.*]` tables.
+fn package_dependency_table_keys(doc: &toml_edit::DocumentMut) -> Vec<Vec<String>> {
+    const TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+    let mut keys: Vec<_> = TABLES.iter().map(|t| vec![t.to_string()]).collect();
+    for (cfg, _) in doc
+        .get("target")
+        .and_then(|t| t.as_table_like())
+        .into_iter()
+        .flat_map(|t| t.iter())
+    {
+        for table in TABLES {
+            keys.push(vec!["target".into(), cfg.into(), table.into()]);
+        }
+    }
+    keys
+}
+
+/// Dependency table at `keys` not sorted by the configured `dependency-order`, sorted in `doc`.
+fn unordered_dependencies(
+    doc: &mut toml_edit::DocumentMut,
+    keys: &[String],
+    order: &DependencyOrder,
+    findings: &mut Vec<Finding>,
+) {
+    let item_keys: Vec<_> = keys.iter().map(|k| k.as_str()).collect();
+    let Some(table) = doc_item_mut(doc, &item_keys) else {
+        return;
+    };
+    if order.sort(table) {
+        findings.push(
+            Finding::new(
+                DEPENDENCY_ORDER,
+                format!(
+                    "{} not in the configured dependency order",
+                    format!("[{}]", keys.join(".")).bold()
+                ),
+            )
+            .fixable()
+            .at(keys),
+        );
+    }
+}
+
+/// `version`, `edition` & `rust-version` of the package table at `keys` with leading or
+/// trailing whitespace, e.g. `" 1.0 "`, trimmed in `doc`.
+fn padded_package_fields(
+    doc: &mut toml_edit::DocumentMut,
+    keys: &[&str],
+    findings: &mut Vec<Finding>,
+) {
+    let Some(package) = doc_item_mut(doc, keys).and_then(|p| p.as_table_like_mut()) else {
+        return;
+    };
+    for field in ["version", "edition", "rust-version"] {
+        let Some(value) = package.get_mut(field).and_then(|v| v.as_value_mut()) else {
+            continue;
+        };
+        if let Some(padded) = trim_padded(value) {
+            findings.push(
+                Finding::new(
+                    PADDED_STRING,
+                    format!("Padded {}", format!("{field} = {padded:?}").bold()),
+                )
+                .fixable()
+                .at(keys.iter().copied().chain([field])),
+            );
+        }
+    }
+}
+
+/// Dependency version requirements in the table at `keys` with leading or trailing
+/// whitespace, e.g. `serde = " 1.0 "`, trimmed in `doc`.
+fn padded_dependency_versions(
+    doc: &mut toml_edit::DocumentMut,
+    keys: &[String],
+    findings: &mut Vec<Finding>,
+) {
+    let item_keys: Vec<_> = keys.iter().map(|k| k.as_str()).collect();
+    let Some(deps) = doc_item_mut(doc, &item_keys).and_then(|t| t.as_table_like_mut()) else {
+        return;
+    };
+    for (name, dep) in deps.iter_mut() {
+        let version = match dep.is_table_like() {
+            true => dep
+                .as_table_like_mut()
+                .and_then(|d| d.get_mut("version"))
+                .and_then(|v| v.as_value_mut()),
+            false => dep.as_value_mut(),
+        };
+        let Some(padded) = version.and_then(trim_padded) else {
+            continue;
+        };
+        findings.push(
+            Finding::new(
+                PADDED_STRING,
+                format!(
+                    "Padded version {} for dependency {}",
+                    format!("{padded:?}").bold(),
+                    name.get().bold()
+                ),
+            )
+            .fixable()
+            .at(keys.iter().map(|k| k.as_str()).chain([name.get()])),
+        );
+    }
+}
+
+/// Trim a string value with leading or trailing whitespace, returning the original.
+fn trim_padded(value: &mut toml_edit::Value) -> Option<String> {
+    let padded = value.as_str().filter(|s| s.trim() != *s)?.to_owned();
+    let decor = value.decor().clone();
+    *value = padded.trim().into();
+    *value.decor_mut() = decor;
+    Some(padded)
+}
+
+/// Dependencies in the table at `keys` setting `optional = false` (default).
+fn redundant_optional_false(
+    doc: &mut toml_edit::DocumentMut,
+    keys: &[String],
+    findings: &mut Vec<Finding>,
+) {
+    let item_keys: Vec<_> = keys.iter().map(|k| k.as_str()).collect();
+    let Some(deps) = doc_item_mut(doc, &item_keys).and_then(|t| t.as_table_like_mut()) else {
+        return;
+    };
+    for (name, dep) in deps.iter_mut() {
+        let Some(dep) = dep.as_table_like_mut() else {
+            continue;
+        };
+        if dep.get("optional").and_then(|o| o.as_bool()) == Some(false) {
+            findings.push(
+                Finding::new(
+                    REDUNDANT_OPTIONAL_FALSE,
+                    format!(
+                        "Redundant {} (default) for dependency {}",
+                        "optional = false".bold(),
+                        name.get().bold()
+                    ),
+                )
+                .fixable()
+                .at(keys
+                    .iter()
+                    .map(|k| k.as_str())
+                    .chain([name.get(), "optional"])),
+            );
+            remove_dep_key(dep, "optional");
+        }
+    }
+}
+
+/// Remove the dependency `key`, keeping the closing `{ .. }` spacing of inline tables.
+fn remove_dep_key(dep: &mut dyn toml_edit::TableLike, key: &str) {
+    let was_last = dep.iter().last().is_some_and(|(k, _)| k == key);
+    let Some(removed) = dep.remove(key) else {
+        return;
+    };
+    let suffix = removed
+        .as_value()
+        .and_then(|v| v.decor().suffix())
+        .filter(|s| s.as_str().is_some_and(|s| s.trim().is_empty()))
+        .cloned();
+    if let (true, Some(suffix)) = (was_last, suffix) {
+        if let Some(last) = dep.iter_mut().last().and_then(|(_, v)| v.as_value_mut()) {
+            last.decor_mut().set_suffix(suffix);
+        }
+    }
+}
+
+/// Dependencies in the table at `keys` with an empty `features = []`.
+fn empty_dependency_features(
+    doc: &mut toml_edit::DocumentMut,
+    keys: &[String],
+    findings: &mut Vec<Finding>,
+) {
+    let item_keys: Vec<_> = keys.iter().map(|k| k.as_str()).collect();
+    let Some(deps) = doc_item_mut(doc, &item_keys).and_then(|t| t.as_table_like_mut()) else {
+        return;
+    };
+    for (name, dep) in deps.iter_mut() {
+        let Some(dep) = dep.as_table_like_mut() else {
+            continue;
+        };
+        let empty = dep
+            .get("features")
+            .and_then(|f| f.as_array())
+            .is_some_and(|f| f.is_empty());
+        if empty {
+            findings.push(
+                Finding::new(
+                    EMPTY_DEPENDENCY_FEATURES,
+                    format!(
+                        "Redundant empty {} for dependency {}",
+                        "features = []".bold(),
+                        name.get().bold()
+                    ),
+                )
+                .fixable()
+                .at(keys
+                    .iter()
+                    .map(|k| k.as_str())
+                    .chain([name.get(), "features"])),
+            );
+            remove_dep_key(dep, "features");
+        }
+    }
+}
+
+/// Profiles cargo ignores, i.e. `[profile]` in non-root members & profiles nested in
+/// `[workspace]` or `[package]` rather than top-level `[profile]` of the workspace root.
+fn ignored_profiles(doc: &toml_edit::DocumentMut, is_root: bool, findings: &mut Vec<Finding>) {
+    let mut tables = vec![
+        (
+            vec!["workspace", "profile"],
+            "profiles must be top-level [profile] tables",
+        ),
+        (
+            vec!["package", "profile"],
+            "profiles must be top-level [profile] tables",
+        ),
+    ];
+    if !is_root {
+        tables.push((vec!["profile"], "profiles only apply in the workspace root"));
+    }
+
+    for (keys, reason) in tables {
+        let Some(profiles) = keys
+            .iter()
+            .try_fold(doc.as_item(), |item, k| item.get(k))
+            .and_then(|p| p.as_table_like())
+        else {
+            continue;
+        };
+        for (name, _) in profiles.iter() {
+            let profile = keys.iter().copied().chain([name]).collect::<Vec<_>>();
+            findings.push(
+                Finding::new(
+                    IGNORED_PROFILE,
+                    format!(
+                        "Ignored profile {}, {reason}",
+                        format!("[{}]", profile.join(".")).bold()
+                    ),
+                )
+                .at(&profile),
+            );
+        }
+    }
+}
+
+/// `package.resolver` in a non-root member, where cargo ignores it.
+fn ineffective_resolver(doc: &mut toml_edit::DocumentMut, findings: &mut Vec<Finding>) {
+    let Some(package) = doc.get_mut("package").and_then(|t| t.as_table_like_mut()) else {
+        return;
+    };
+    if package.remove("resolver").is_some() {
+        findings.push(
+            Finding::new(
+                INEFFECTIVE_RESOLVER,
+                format!(
+                    "Ineffective {} in a non-root member, set it in the workspace root",
+                    "package.resolver".bold()
+                ),
+            )
+            .fixable()
+            .at(["package", "resolver"]),
+        );
+    }
+}
+
+/// `license = "MIT/Apache-2.0"` deprecated `/` separator, replaced with SPDX `OR`.
+fn deprecated_license_slash(
+    doc: &mut toml_edit::DocumentMut,
+    keys: &[&str],
+    findings: &mut Vec<Finding>,
+) {
+    let Some(value) = doc_item_mut(doc, keys).and_then(|v| v.as_value_mut()) else {
+        return;
+    };
+    let Some(license) = value.as_str().filter(|l| l.contains('/')) else {
+        return;
+    };
+
+    let spdx = spdx_license(license);
+    findings.push(
+        Finding::new(
+            DEPRECATED_LICENSE_SLASH,
+            format!(
+                "Deprecated license {}, use {}",
+                format!("{license:?}").bold(),
+                format!("{spdx:?}").bold(),
+            ),
+        )
+        .fixable()
+        .at(keys),
+    );
+    let decor = value.decor().clone();
+    *value = spdx.into();
+    *value.decor_mut() = decor;
+}
+
+/// `"MIT/Apache-2.0"` -> `"MIT OR Apache-2.0"`, keeping leading & trailing whitespace.
+fn spdx_license(license: &str) -> String {
+    let content = license.trim();
+    let start = license.len() - license.trim_start().len();
+    let end = start + content.len();
+    let expr = content
+        .split('/')
+        .map(|l| l.trim())
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    format!("{}{expr}{}", &license[..start], &license[end..])
+}
+
+/// `documentation` set to `https://docs.rs/<name>`, the default for published crates.
+fn default_documentation_url(doc: &mut toml_edit::DocumentMut, findings: &mut Vec<Finding>) {
+    let Some(package) = doc.get_mut("package").and_then(|p| p.as_table_like_mut()) else {
+        return;
+    };
+    let Some(name) = package.get("name").and_then(|n| n.as_str()) else {
+        return;
+    };
+    let Some(url) = package.get("documentation").and_then(|d| d.as_str()) else {
+        return;
+    };
+    let default_url = format!("https://docs.rs/{name}");
+    if url.trim_end_matches('/') != default_url {
+        return;
+    }
+    findings.push(
+        Finding::new(
+            DEFAULT_DOCUMENTATION,
+            format!(
+                "Redundant {} (default)",
+                format!("documentation = {url:?}").bold()
+            ),
+        )
+        .fixable()
+        .at(["package", "documentation"]),
+    );
+    package.remove("documentation");
+}
+
+/// `[build-dependencies]` of a package without a build script, removed if `remove`.
+fn build_deps_without_build_script(
+    doc: &mut toml_edit::DocumentMut,
+    dir: &cargo_metadata::camino::Utf8Path,
+    remove: bool,
+    findings: &mut Vec<Finding>,
+) {
+    let Some(package) = doc.get("package") else {
+        return;
+    };
+    let has_build_script = match package.get("build") {
+        Some(build) if build.as_bool() == Some(false) => false,
+        Some(build) => build
+            .as_str()
+            .map_or(dir.join("build.rs").exists(), |script| {
+                dir.join(script).exists()
+            }),
+        None => dir.join("build.rs").exists(),
+    };
+    if has_build_script {
+        return;
+    }
+
+    let mut tables = vec![vec!["build-dependencies".to_owned()]];
+    for (cfg, _) in doc
+        .get("target")
+        .and_then(|t| t.as_table_like())
+        .into_iter()
+        .flat_map(|t| t.iter())
+    {
+        tables.push(vec![
+            "target".into(),
+            cfg.into(),
+            "build-dependencies".into(),
+        ]);
+    }
+
+    for keys in tables {
+        let (parent_keys, [table]) = keys.split_at(keys.len() - 1) else {
+            continue;
+        };
+        let parent_keys: Vec<_> = parent_keys.iter().map(|k| k.as_str()).collect();
+        let Some(parent) = doc_item_mut(doc, &parent_keys).and_then(|p| p.as_table_like_mut())
+        else {
+            continue;
+        };
+        if !parent.contains_key(table) {
+            continue;
+        }
+
+        let finding = Finding::new(
+            BUILD_DEPS_WITHOUT_SCRIPT,
+            format!(
+                "Likely unused {} without a build script",
+                format!("[{}]", keys.join(".")).bold()
+            ),
+        )
+        .at(&keys);
+        findings.push(match remove {
+            true => finding.fixable(),
+            false => finding,
+        });
+        if remove {
+            parent.remove(table);
+        }
+    }
+}
+
+/// Member dependencies that could inherit a `[workspace.dependencies]` entry but don't.
+fn should_inherit_workspace_deps(root: &Manifest, member: &Manifest, findings: &mut Vec<Finding>) {
+    let Some(ws) = &root.workspace else {
+        return;
+    };
+
+    for table in dependency_tables(member) {
+        for (name, dep) in table.deps {
+            if matches!(dep, cargo_toml::Dependency::Inherited(_))
+                || !ws.dependencies.contains_key(name)
+            {
+                continue;
+            }
+            findings.push(
+                Finding::new(
+                    SHOULD_INHERIT,
+                    format!(
+                        "{} {} should inherit the workspace dependency, e.g. {}",
+                        capitalize(table.item_name),
+                        name.bold(),
+                        format!("{name}.workspace = true").bold(),
+                    ),
+                )
+                .at(table.keys_to(name)),
+            );
+        }
+    }
+}
+
+/// A dependency table, e.g. `[dependencies]` or `[target.'cfg(unix)'.dev-dependencies]`.
+struct DepTable<'a> {
+    deps: &'a cargo_toml::DepsSet,
+    /// Toml key path of the table.
+    keys: Vec<&'a str>,
+    item_name: &'static str,
+}
+
+impl<'a> DepTable<'a> {
+    /// Toml key path of the dependency `name` in this table.
+    fn keys_to(&self, name: &'a str) -> Vec<&'a str> {
+        let mut keys = self.keys.clone();
+        keys.push(name);
+        keys
+    }
+}
+
+/// All dependency tables, including target specific ones.
+fn dependency_tables(manifest: &Manifest) -> impl Iterator<Item = DepTable<'_>> {
+    [
+        (&manifest.dependencies, None, "dependencies"),
+        (&manifest.dev_dependencies, None, "dev-dependencies"),
+        (&manifest.build_dependencies, None, "build-dependencies"),
+    ]
+    .into_iter()
+    .chain(manifest.target.iter().flat_map(|(target, t)| {
+        [
+            (&t.dependencies, Some(target.as_str()), "dependencies"),
+            (
+                &t.dev_dependencies,
+                Some(target.as_str()),
+                "dev-dependencies",
+            ),
+            (
+                &t.build_dependencies,
+                Some(target.as_str()),
+                "build-dependencies",
+            ),
+        ]
+    }))
+    .map(|(deps, target, table)| DepTable {
+        deps,
+        keys: match target {
+            Some(target) => vec!["target", target, table],
+            None => vec![table],
+        },
+        item_name: match table {
+            "dev-dependencies" => "dev-dependency",
+            "build-dependencies" => "build-dependency",
+            _ => "dependency",
+        },
+    })
+}
+
+/// Git dependencies specifying more than one of `rev`, `tag` & `branch`.
+fn conflicting_git_refs(table: &DepTable<'_>, findings: &mut Vec<Finding>) {
+    for (name, dep) in table.deps {
+        let Some(d) = dep.detail().filter(|d| d.git.is_some()) else {
+            continue;
+        };
+        let refs: Vec<_> = [("rev", &d.rev), ("tag", &d.tag), ("branch", &d.branch)]
+            .into_iter()
+            .filter(|(_, v)| v.is_some())
+            .map(|(k, _)| k)
+            .collect();
+        if refs.len() > 1 {
+            findings.push(
+                Finding::new(
+                    CONFLICTING_GIT_REFS,
+                    format!(
+                        "Git {} {} specifies conflicting {}",
+                        table.item_name,
+                        name.bold(),
+                        refs.join(" & ").bold(),
+                    ),
+                )
+                .at(table.keys_to(name)),
+            );
+        }
+    }
+}
+
+/// Version requirements no version can satisfy, e.g. `>=2, <1`.
+fn unsatisfiable_version_reqs(table: &DepTable<'_>, findings: &mut Vec<Finding>) {
+    for (name, dep) in table.deps {
+        let Ok(req) = dep.try_req() else {
+            continue; // inherited
+        };
+        let Ok(parsed) = req.parse::<semver::VersionReq>() else {
+            continue;
+        };
+        if version::is_unsatisfiable(&parsed) {
+            findings.push(
+                Finding::new(
+                    UNSATISFIABLE_VERSION,
+                    format!(
+                        "Version requirement {} of {} {} can never match",
+                        format!("{req:?}").bold(),
+                        table.item_name,
+                        name.bold(),
+                    ),
+                )
+                .at(table.keys_to(name)),
+            );
+        }
+    }
+}
+
+struct WorkspaceDirs {
+    root: Utf8PathBuf,
+    members: Vec<Utf8PathBuf>,
+}
+
+/// Path dependencies outside the workspace root that aren't workspace members.
+///
+/// Informational, these can't benefit from workspace inheritance.
+fn path_deps_outside_workspace(
+    table: &DepTable<'_>,
+    dir: &cargo_metadata::camino::Utf8Path,
+    workspace: &WorkspaceDirs,
+    findings: &mut Vec<Finding>,
+) {
+    for (name, dep) in table.deps {
+        let Some(path) = dep.detail().and_then(|d| d.path.as_deref()) else {
+            continue;
+        };
+        let resolved = normalize_path(&dir.join(path));
+        if resolved.starts_with(&workspace.root) || workspace.members.contains(&resolved) {
+            continue;
+        }
+        findings.push(
+            Finding::new(
+                PATH_OUTSIDE_WORKSPACE,
+                format!(
+                    "Path {} {} at {} is outside the workspace root",
+                    table.item_name,
+                    name.bold(),
+                    format!("{path:?}").bold(),
+                ),
+            )
+            .info()
+            .at(table.keys_to(name)),
+        );
+    }
+}
+
+/// Path dependencies on other workspace members not using workspace inheritance,
+/// enabled by config `inherit-member-deps`.
+fn uninherited_member_deps(
+    table: &DepTable<'_>,
+    dir: &cargo_metadata::camino::Utf8Path,
+    meta: &cargo_metadata::Metadata,
+    findings: &mut Vec<Finding>,
+) {
+    let members = meta.workspace_packages();
+    let Some(this) = members
+        .iter()
+        .find(|m| m.manifest_path.parent() == Some(dir))
+    else {
+        return;
+    };
+    for (name, dep) in table.deps {
+        let Some(path) = dep.detail().and_then(|d| d.path.as_deref()) else {
+            continue;
+        };
+        let resolved = normalize_path(&dir.join(path));
+        let Some(target) = members.iter().find(|m| {
+            m.manifest_path
+                .parent()
+                .is_some_and(|dir| normalize_path(dir) == resolved)
+        }) else {
+            continue;
+        };
+        findings.push(
+            Finding::new(
+                UNINHERITED_MEMBER_DEP,
+                format!(
+                    "Member {} depends on member {} with {}, use a workspace dependency",
+                    this.name.bold(),
+                    target.name.bold(),
+                    format!("{name} = {{ path = {path:?} }}").bold(),
+                ),
+            )
+            .at(table.keys_to(name)),
+        );
+    }
+}
+
+/// Dependencies inheriting a workspace dependency with a path to the member itself.
+fn inherited_self_dependencies(
+    table: &DepTable<'_>,
+    root: &Manifest,
+    meta: &cargo_metadata::Metadata,
+    member_dir: &cargo_metadata::camino::Utf8Path,
+    findings: &mut Vec<Finding>,
+) {
+    let Some(ws) = &root.workspace else {
+        return;
+    };
+    let member_dir = normalize_path(member_dir);
+    for (name, dep) in table.deps {
+        let cargo_toml::Dependency::Inherited(dep) = dep else {
+            continue;
+        };
+        if !dep.workspace {
+            continue;
+        }
+        let Some(path) = ws
+            .dependencies
+            .get(name)
+            .and_then(|d| d.detail())
+            .and_then(|d| d.path.as_deref())
+        else {
+            continue;
+        };
+        if normalize_path(&meta.workspace_root.join(path)) != member_dir {
+            continue;
+        }
+        findings.push(
+            Finding::new(
+                SELF_DEPENDENCY,
+                format!(
+                    "Inherited {} {} is this member itself, workspace dependency path {}",
+                    table.item_name,
+                    name.bold(),
+                    format!("{path:?}").bold(),
+                ),
+            )
+            .at(table.keys_to(name)),
+        );
+    }
+}
+
+/// `publish = false` members in `default-members` alongside publishable members,
+/// enabled by config `publishable-default-members`.
+fn unpublished_default_members(
+    root: &Manifest,
+    meta: &cargo_metadata::Metadata,
+    findings: &mut Vec<Finding>,
+) {
+    // cargo reports all members as default when default-members is unset
+    let default_members_set = root
+        .workspace
+        .as_ref()
+        .is_some_and(|ws| !ws.default_members.is_empty());
+    if !default_members_set || !meta.workspace_default_members.is_available() {
+        return;
+    }
+    let defaults: Vec<_> = meta
+        .packages
+        .iter()
+        .filter(|p| meta.workspace_default_members.contains(&p.id))
+        .collect();
+    // publish = false is an empty list of allowed registries
+    let unpublished: Vec<_> = defaults
+        .iter()
+        .filter(|p| p.publish.as_ref().is_some_and(|r| r.is_empty()))
+        .map(|p| p.name.as_str())
+        .collect();
+    if unpublished.is_empty() || unpublished.len() == defaults.len() {
+        return;
+    }
+    findings.push(
+        Finding::new(
+            UNPUBLISHED_DEFAULT_MEMBER,
+            format!(
+                "{} includes publish = false member(s) {}",
+                "default-members".bold(),
+                format!("{unpublished:?}").bold(),
+            ),
+        )
+        .at(["workspace", "default-members"]),
+    );
+}
+
+/// `[target.*]` dependencies adding nothing to the same unconditional dependency,
+/// removed in `doc`.
+fn redundant_target_dependencies(
+    member: &Manifest,
+    doc: &mut toml_edit::DocumentMut,
+    findings: &mut Vec<Finding>,
+) {
+    let tables: Vec<_> = dependency_tables(member).collect();
+    for target_table in tables.iter().filter(|t| t.keys[0] == "target") {
+        let Some(base) = tables.iter().find(|t| t.keys == target_table.keys[2..]) else {
+            continue;
+        };
+        for (name, dep) in target_table.deps {
+            let Some(base_dep) = base.deps.get(name) else {
+                continue;
+            };
+            if !adds_nothing(dep, base_dep) {
+                continue;
+            }
+            findings.push(
+                Finding::new(
+                    REDUNDANT_TARGET_DEP,
+                    format!(
+                        "Redundant {} {} in {}, already in {}",
+                        target_table.item_name,
+                        name.bold(),
+                        table_header(doc, &target_table.keys).bold(),
+                        table_header(doc, &base.keys).bold(),
+                    ),
+                )
+                .fixable()
+                .at(target_table.keys_to(name)),
+            );
+            remove_target_dependency(doc, &target_table.keys, name);
+        }
+    }
+}
+
+/// Remove the dependency `name` from the target table at `keys`, e.g.
+/// `["target", "cfg(unix)", "dependencies"]`, then the tables it leaves empty.
+fn remove_target_dependency(doc: &mut toml_edit::DocumentMut, keys: &[&str], name: &str) {
+    for depth in (1..=keys.len()).rev() {
+        let Some(table) = doc_item_mut(doc, &keys[..depth]).and_then(|t| t.as_table_like_mut())
+        else {
+            return;
+        };
+        table.remove(keys.get(depth).copied().unwrap_or(name));
+        if !table.is_empty() {
+            return;
+        }
+    }
+}
+
+/// Table header of the toml key path with keys quoted as written in `doc`,
+/// e.g. `[target.'cfg(unix)'.dependencies]`.
+fn table_header(doc: &toml_edit::DocumentMut, keys: &[&str]) -> String {
+    let mut item = Some(doc.as_item());
+    let keys: Vec<_> = keys
+        .iter()
+        .map(
+            |key| match item.and_then(|i| i.as_table_like()?.get_key_value(key)) {
+                Some((k, next)) => {
+                    item = Some(next);
+                    k.display_repr().into_owned()
+                }
+                None => {
+                    item = None;
+                    toml_edit::Key::new(*key).display_repr().into_owned()
+                }
+            },
+        )
+        .collect();
+    format!("[{}]", keys.join("."))
+}
+
+/// Whether `dep` adds nothing to `base`, i.e. same source, version & optionality,
+/// no extra features & default features only if `base` has them.
+fn adds_nothing(dep: &cargo_toml::Dependency, base: &cargo_toml::Dependency) -> bool {
+    use cargo_toml::Dependency;
+
+    type Source<'a> = (
+        Option<&'a str>,
+        Option<&'a str>,
+        Option<&'a str>,
+        Option<&'a str>,
+        Option<&'a str>,
+        Option<&'a str>,
+        Option<&'a str>,
+    );
+    fn source(d: &Dependency) -> Source<'_> {
+        d.detail()
+            .map(|d| {
+                (
+                    d.path.as_deref(),
+                    d.git.as_deref(),
+                    d.branch.as_deref(),
+                    d.tag.as_deref(),
+                    d.rev.as_deref(),
+                    d.registry.as_deref(),
+                    d.package.as_deref(),
+                )
+            })
+            .unwrap_or_default()
+    }
+    let default_features = |d: &Dependency| d.detail().is_none_or(|d| d.default_features);
+
+    let same_source = match (dep, base) {
+        (Dependency::Inherited(d), Dependency::Inherited(b)) => d.workspace == b.workspace,
+        (Dependency::Inherited(_), _) | (_, Dependency::Inherited(_)) => false,
+        _ => {
+            dep.try_req().ok() == base.try_req().ok()
+                && source(dep) == source(base)
+                && (!default_features(dep) || default_features(base))
+        }
+    };
+    same_source
+        && dep.optional() == base.optional()
+        && dep
+            .req_features()
+            .iter()
+            .all(|f| base.req_features().contains(f))
+}
+
+/// `[patch]` entries with a path to a workspace member. These are only needed to replace
+/// registry dependencies on the crate, e.g. from non-member dependencies, members can depend
+/// on the member directly with a path or workspace dependency.
+fn member_patches(root: &Manifest, meta: &cargo_metadata::Metadata, findings: &mut Vec<Finding>) {
+    let members = meta.workspace_packages();
+    for (registry, deps) in &root.patch {
+        for (name, dep) in deps {
+            let Some(path) = dep.detail().and_then(|d| d.path.as_deref()) else {
+                continue;
+            };
+            let resolved = normalize_path(&meta.workspace_root.join(path));
+            let Some(member) = members.iter().find(|m| {
+                m.manifest_path
+                    .parent()
+                    .is_some_and(|dir| normalize_path(dir) == resolved)
+            }) else {
+                continue;
+            };
+            findings.push(
+                Finding::new(
+                    REDUNDANT_MEMBER_PATCH,
+                    format!(
+                        "Patch {} at {} is workspace member {}, redundant unless registry \
+                         dependencies on it need replacing",
+                        name.bold(),
+                        format!("{path:?}").bold(),
+                        member.name.bold(),
+                    ),
+                )
+                .at(["patch", registry, name]),
+            );
+        }
+    }
+}
+
+/// Lexically resolve `.` & `..` components.
+fn normalize_path(path: &cargo_metadata::camino::Utf8Path) -> Utf8PathBuf {
+    use cargo_metadata::camino::Utf8Component;
+
+    let mut out = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::CurDir => {}
+            Utf8Component::ParentDir => {
+                out.pop();
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+struct MixedSources<'a> {
+    dep: &'a str,
+    /// Members using a path dependency.
+    path: Vec<&'a str>,
+    /// Members using a registry dependency.
+    registry: Vec<&'a str>,
+}
+
+/// Crates declared as a path dependency in some members & from the registry in others.
+fn mixed_dependency_sources(members: &[Manifest]) -> Vec<MixedSources<'_>> {
+    let mut sources = std::collections::BTreeMap::<&str, MixedSources>::new();
+
+    for member in members {
+        let member_name = member.package.as_ref().map_or("?", |p| p.name());
+        let deps = member
+            .dependencies
+            .iter()
+            .chain(&member.dev_dependencies)
+            .chain(&member.build_dependencies)
+            .chain(member.target.values().flat_map(|t| {
+                t.dependencies
+                    .iter()
+                    .chain(&t.dev_dependencies)
+                    .chain(&t.build_dependencies)
+            }));
+
+        for (name, dep) in deps {
+            let crate_name = dep.package().unwrap_or(name);
+            let entry = sources.entry(crate_name).or_insert_with(|| MixedSources {
+                dep: crate_name,
+                path: vec![],
+                registry: vec![],
+            });
+            let members = match dep {
+                cargo_toml::Dependency::Inherited(_) => continue,
+                cargo_toml::Dependency::Detailed(d) if d.path.is_some() => &mut entry.path,
+                cargo_toml::Dependency::Detailed(d) if d.git.is_some() => continue,
+                _ => &mut entry.registry,
+            };
+            if !members.contains(&member_name) {
+                members.push(member_name);
+            }
+        }
+    }
+
+    sources
+        .into_values()
+        .filter(|s| !s.path.is_empty() && !s.registry.is_empty())
+        .collect()
+}
+
+struct DivergentVersions<'a> {
+    dep: &'a str,
+    /// Distinct registry version requirements & the members using each.
+    reqs: Vec<(&'a str, semver::VersionReq, Vec<&'a str>)>,
+}
+
+/// Crates used from the registry by multiple members with distinct version requirements.
+fn divergent_version_reqs(members: &[Manifest]) -> Vec<DivergentVersions<'_>> {
+    let mut versions = std::collections::BTreeMap::<&str, DivergentVersions>::new();
+
+    for member in members {
+        let member_name = member.package.as_ref().map_or("?", |p| p.name());
+        let deps = member
+            .dependencies
+            .iter()
+            .chain(&member.dev_dependencies)
+            .chain(&member.build_dependencies)
+            .chain(member.target.values().flat_map(|t| {
+                t.dependencies
+                    .iter()
+                    .chain(&t.dev_dependencies)
+                    .chain(&t.build_dependencies)
+            }));
+
+        for (name, dep) in deps {
+            let version = match dep {
+                cargo_toml::Dependency::Simple(v) => v,
+                cargo_toml::Dependency::Detailed(d) if d.path.is_none() && d.git.is_none() => {
+                    match &d.version {
+                        Some(v) => v,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            let Ok(req) = semver::VersionReq::parse(version) else {
+                continue;
+            };
+            let crate_name = dep.package().unwrap_or(name);
+            let reqs = &mut versions
+                .entry(crate_name)
+                .or_insert_with(|| DivergentVersions {
+                    dep: crate_name,
+                    reqs: vec![],
+                })
+                .reqs;
+            let members = match reqs.iter().position(|(_, r, _)| *r == req) {
+                Some(idx) => &mut reqs[idx].2,
+                None => {
+                    reqs.push((version, req, vec![]));
+                    &mut reqs.last_mut().unwrap().2
+                }
+            };
+            if !members.contains(&member_name) {
+                members.push(member_name);
+            }
+        }
+    }
+
+    versions
+        .into_values()
+        .filter(|v| v.reqs.len() > 1)
+        .collect()
+}
+
+/// Lint a member manifest, applying fixes to `doc`.
+fn lint_manifest(
+    root: &Manifest,
+    member: &Manifest,
+    doc: &mut toml_edit::DocumentMut,
+) -> Vec<Finding> {
+    let mut findings = vec![];
+
+    for (name, ws_dep) in root.workspace.iter().flat_map(|ws| &ws.dependencies) {
+        for (deps, table, item_name) in [
+            (&member.dependencies, "dependencies", "dependency"),
+            (
+                &member.dev_dependencies,
+                "dev-dependencies",
+                "dev-dependency",
+            ),
+        ] {
+            let Some(cargo_toml::Dependency::Inherited(dep)) = deps.get(name) else {
+                continue;
+            };
+            if !dep.workspace {
+                continue;
+            }
+            let doc_deps = &mut doc[table];
+
+            let found = dependency_with_redundant_workspace_features(
+                name, ws_dep, dep, doc_deps, item_name,
+            )
+            .into_iter()
+            .chain(workspace_dependency_with_default_features_set(
+                name, doc_deps, item_name,
+            ));
+            findings.extend(found.map(|f| f.at([table, name])));
+        }
+    }
+
+    findings
+}
+
+/// workspace=true dependencies setting default-features has no effect.
+fn workspace_dependency_with_default_features_set(
+    dep_name: &str,
+    doc_deps: &mut toml_edit::Item,
+    item_name: &str,
+) -> Option<Finding> {
+    let table = doc_deps[dep_name].as_table_like_mut()?;
+    let fixes =
+        table.remove("default-features").is_some() || table.remove("default_features").is_some();
+
+    fixes.then(|| {
+        Finding::new(
+            REDUNDANT_DEFAULT_FEATURES,
+            format!(
+                "Redundant default-features set in workspace {item_name} {}",
+                dep_name.bold()
+            ),
+        )
+        .fixable()
+    })
+}
+
+/// workspace=true dependencies do not need to restate the workspace features.
+fn dependency_with_redundant_workspace_features(
+    dep_name: &str,
+    ws_dep: &cargo_toml::Dependency,
+    dep: &cargo_toml::InheritedDependencyDetail,
+    doc_deps: &mut toml_edit::Item,
+    item_name: &str,
+) -> Option<Finding> {
+    let redundant_features: Vec<_> = dep
+        .features
+        .iter()
+        .filter(|f| ws_dep.req_features().contains(f))
+        .map(|s| s.as_str())
+        .collect();
+
+    if redundant_features.is_empty() {
+        return None;
+    }
+
+    let finding = Finding::new(
+        REDUNDANT_WORKSPACE_FEATURES,
+        format!(
+            "Redundant feature(s) {} for workspace {item_name} {}",
+            format!("{redundant_features:?}").bold(),
+            dep_name.bold(),
+        ),
+    )
+    .removing_features(&redundant_features);
+
+    let feats = doc_deps[dep_name]["features"].as_array_mut().unwrap();
+    let rm_idx: Vec<_> = feats
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.as_str().is_some_and(|s| redundant_features.contains(&s)))
+        .map(|(idx, _)| idx)
+        .collect();
+    for idx in rm_idx.into_iter().rev() {
+        feats.remove(idx);
+    }
+
+    if feats.is_empty() {
+        doc_deps[dep_name]
+            .as_table_like_mut()
+            .unwrap()
+            .remove("features");
+    }
+
+    Some(finding)
+}
+
+/// Optional dependencies never referenced with `dep:` get an implicit feature of the same
+/// name, features relying on that will break once the implicit feature is gone.
+///
+/// Diagnostic only, doesn't contribute to the exit code.
+fn implicit_optional_dependency_features(member: &Manifest, findings: &mut Vec<Finding>) {
+    let optional_deps = dependency_tables(member)
+        .filter(|t| t.item_name != "dev-dependency")
+        .flat_map(|t| {
+            t.deps
+                .iter()
+                .filter(|(_, dep)| dep.optional())
+                .map(move |(name, _)| (t.keys_to(name), name))
+        });
+
+    let feature_values = || member.features.values().flatten();
+
+    for (keys, dep) in optional_deps {
+        let explicit = format!("dep:{dep}");
+        if feature_values().any(|f| *f == explicit) {
+            continue; // no implicit feature is created
+        }
+
+        if member.features.contains_key(dep) {
+            findings.push(
+                Finding::new(
+                    IMPLICIT_OPTIONAL_FEATURE,
+                    format!(
+                        "Feature {} shares a name with implicit optional dependency feature",
+                        dep.bold()
+                    ),
+                )
+                .info()
+                .at(["features", dep]),
+            );
+        } else {
+            findings.push(
+                Finding::new(
+                    IMPLICIT_OPTIONAL_FEATURE,
+                    format!("Optional dependency {} has an implicit feature", dep.bold()),
+                )
+                .info()
+                .at(keys),
+            );
+        }
+
+        for (feature, values) in &member.features {
+            if values.iter().any(|v| v == dep) {
+                findings.push(
+                    Finding::new(
+                        IMPLICIT_OPTIONAL_FEATURE,
+                        format!(
+                            "Feature {} references implicit optional dependency feature {}",
+                            feature.bold(),
+                            dep.bold(),
+                        ),
+                    )
+                    .info()
+                    .at(["features", feature]),
+                );
+            }
+        }
+    }
+}
+
+/// `dep:` feature references must point at optional dependencies.
+fn non_optional_dep_features(member: &Manifest, findings: &mut Vec<Finding>) {
+    for (feature, values) in &member.features {
+        for dep_name in values.iter().filter_map(|v| v.strip_prefix("dep:")) {
+            let optional = dependency_tables(member)
+                .filter(|t| t.item_name != "dev-dependency")
+                .filter_map(|t| t.deps.get(dep_name))
+                .any(|dep| dep.optional());
+            if !optional {
+                findings.push(
+                    Finding::new(
+                        NON_OPTIONAL_DEP_FEATURE,
+                        format!(
+                            "Feature {} references {} which is not an optional dependency",
+                            feature.bold(),
+                            format!("dep:{dep_name}").bold(),
+                        ),
+                    )
+                    .at(["features", feature]),
+                );
+            }
+        }
+    }
+}
+
+/// Cycles of `[features]` enabling each other, e.g. `a = ["b"]`, `b = ["a"]`.
+fn circular_features(member: &Manifest, findings: &mut Vec<Finding>) {
+    fn visit<'a>(
+        feature: &'a str,
+        features: &'a std::collections::BTreeMap<String, Vec<String>>,
+        path: &mut Vec<&'a str>,
+        done: &mut Vec<&'a str>,
+        cycles: &mut Vec<Vec<&'a str>>,
+    ) {
+        if let Some(idx) = path.iter().position(|f| *f == feature) {
+            cycles.push(path[idx..].to_vec());
+            return;
+        }
+        if done.contains(&feature) {
+            return;
+        }
+        path.push(feature);
+        for enabled in features.get(feature).into_iter().flatten() {
+            if features.contains_key(enabled) {
+                visit(enabled, features, path, done, cycles);
+            }
+        }
+        path.pop();
+        done.push(feature);
+    }
+
+    let (mut path, mut done, mut cycles) = (vec![], vec![], vec![]);
+    for feature in member.features.keys() {
+        visit(feature, &member.features, &mut path, &mut done, &mut cycles);
+    }
+
+    for cycle in cycles {
+        let path = cycle
+            .iter()
+            .chain(&cycle[..1])
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        findings.push(
+            Finding::new(
+                CIRCULAR_FEATURES,
+                format!("Circular features {}", path.bold()),
+            )
+            .at(["features", cycle[0]]),
+        );
+    }
+}
+
+/// Features referencing the original crate name of a renamed dependency instead of its key.
+fn renamed_dep_feature_refs(member: &Manifest, findings: &mut Vec<Finding>) {
+    let renames: Vec<_> = dependency_tables(member)
+        .flat_map(|t| t.deps.iter())
+        .filter_map(|(key, dep)| Some((key.as_str(), dep.package()?)))
+        .filter(|(key, package)| key != package)
+        .collect();
+    let is_key = |name: &str| dependency_tables(member).any(|t| t.deps.contains_key(name));
+
+    for (feature, values) in &member.features {
+        for value in values {
+            let referenced = value.strip_prefix("dep:").unwrap_or(value);
+            let referenced = referenced.split('/').next().unwrap_or(referenced);
+            let referenced = referenced.strip_suffix('?').unwrap_or(referenced);
+            if member.features.contains_key(referenced) || is_key(referenced) {
+                continue;
+            }
+            let Some((key, _)) = renames.iter().find(|(_, package)| *package == referenced) else {
+                continue;
+            };
+            findings.push(
+                Finding::new(
+                    RENAMED_DEP_FEATURE,
+                    format!(
+                        "Feature {} references {} by its crate name, use the dependency key {}",
+                        feature.bold(),
+                        value.bold(),
+                        key.bold(),
+                    ),
+                )
+                .at(["features", feature]),
+            );
+        }
+    }
+}
+
+trait PackageIdExt {
+    fn manifest_path(&self) -> Option<Utf8PathBuf>;
+}
+impl PackageIdExt for PackageId {
     fn manifest_path(&self) -> Option<Utf8PathBuf> {
         let fidx = self.repr.find("path+file://")?;
         let mut path = &self.repr[fidx + "path+file://".len()..];
@@ -265,3 +2837,201 @@ impl PackageIdExt for PackageId {
         Some(path.join("Cargo.toml"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn redundant_auto_discovery_true() {
+        let mut doc = "[package]\nname = \"a\"\nautobins = true\nautotests = false\n"
+            .parse()
+            .unwrap();
+        let mut findings = vec![];
+        redundant_auto_discovery(&mut doc, &mut findings);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].keys, ["package", "autobins"]);
+        assert_eq!(
+            doc.to_string(),
+            "[package]\nname = \"a\"\nautotests = false\n"
+        );
+    }
+
+    #[test]
+    fn auto_discovery_false_kept() {
+        let toml = "[package]\nname = \"a\"\nautobins = false\nautoexamples = false\n";
+        let mut doc = toml.parse().unwrap();
+        let mut findings = vec![];
+        redundant_auto_discovery(&mut doc, &mut findings);
+        assert!(findings.is_empty());
+        assert_eq!(doc.to_string(), toml);
+    }
+
+    #[test]
+    fn spdx_licenses() {
+        assert_eq!(spdx_license("MIT/Apache-2.0"), "MIT OR Apache-2.0");
+        assert_eq!(
+            spdx_license("MIT / Apache-2.0/ Zlib"),
+            "MIT OR Apache-2.0 OR Zlib"
+        );
+        assert_eq!(spdx_license(" MIT/Unlicense "), " MIT OR Unlicense ");
+    }
+
+    #[test]
+    fn deprecated_license_slash_fix() {
+        let mut doc = "[package]\nname = \"a\"\nlicense = \"MIT/Apache-2.0\" # dual\n"
+            .parse()
+            .unwrap();
+        let mut findings = vec![];
+        deprecated_license_slash(&mut doc, &["package", "license"], &mut findings);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            doc.to_string(),
+            "[package]\nname = \"a\"\nlicense = \"MIT OR Apache-2.0\" # dual\n"
+        );
+
+        let toml = "[package]\nname = \"a\"\nlicense = \"MIT OR Apache-2.0\"\n";
+        let mut doc = toml.parse().unwrap();
+        let mut findings = vec![];
+        deprecated_license_slash(&mut doc, &["package", "license"], &mut findings);
+        assert!(findings.is_empty());
+        assert_eq!(doc.to_string(), toml);
+    }
+
+    #[test]
+    fn rename_misnamed_feature_refs() {
+        let mut doc = "[features]\n\
+                       a = [\"dep:Serde\", \"Serde/derive\", \"Serde?/rc\", \"Serde_json/std\"]\n\
+                       b = [\"Serde\"]\n"
+            .parse()
+            .unwrap();
+        rename_feature_refs(&mut doc, "Serde", "serde");
+        assert_eq!(
+            doc.to_string(),
+            "[features]\n\
+             a = [\"dep:serde\", \"serde/derive\", \"serde?/rc\", \"Serde_json/std\"]\n\
+             b = [\"serde\"]\n"
+        );
+    }
+
+    #[test]
+    fn rename_feature_refs_keeps_same_named_feature() {
+        let mut doc = "[features]\nSerde = [\"dep:Serde\"]\nb = [\"Serde\"]\n"
+            .parse()
+            .unwrap();
+        rename_feature_refs(&mut doc, "Serde", "serde");
+        assert_eq!(
+            doc.to_string(),
+            "[features]\nSerde = [\"dep:serde\"]\nb = [\"Serde\"]\n"
+        );
+    }
+
+    #[test]
+    fn redundant_optional_false_member() {
+        let mut doc = "[package]\nname = \"a\"\n\n[dependencies]\n\
+                       x = { version = \"1\", optional = false }\n\
+                       y = { version = \"1\", optional = true }\n"
+            .parse()
+            .unwrap();
+        let mut findings = vec![];
+        redundant_optional_false(&mut doc, &["dependencies".to_owned()], &mut findings);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            doc.to_string(),
+            "[package]\nname = \"a\"\n\n[dependencies]\n\
+             x = { version = \"1\" }\ny = { version = \"1\", optional = true }\n"
+        );
+    }
+
+    #[test]
+    fn redundant_optional_false_workspace() {
+        let mut doc = "[workspace.dependencies]\nx = { version = \"1\", optional = false }\n"
+            .parse()
+            .unwrap();
+        let mut findings = vec![];
+        let keys = ["workspace".to_owned(), "dependencies".to_owned()];
+        redundant_optional_false(&mut doc, &keys, &mut findings);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(
+            doc.to_string(),
+            "[workspace.dependencies]\nx = { version = \"1\" }\n"
+        );
+    }
+
+    const ROOT: &str = r#"[workspace]
+members = ["a", "b"]
+
+[workspace.dependencies]
+serde = { version = "1", features = ["derive"] }
+tokio = { version = "1", features = ["macros", "rt"] }
+"#;
+
+    fn ws_info(root: &Manifest) -> WorkspaceInfo<'_> {
+        WorkspaceInfo {
+            root,
+            misnamed_deps: &[],
+            dependency_order: None,
+            include_dev: true,
+            check_whitespace: true,
+        }
+    }
+
+    fn fixed_member(toml: &str) -> Option<String> {
+        let root = Manifest::from_str(ROOT).unwrap();
+        let manifests = BTreeMap::from([("a/Cargo.toml", toml.to_owned())]);
+        fix_manifests(&manifests, &ws_info(&root))
+            .unwrap()
+            .remove("a/Cargo.toml")
+    }
+
+    #[test]
+    fn fix_manifests_in_memory() {
+        let root = Manifest::from_str(ROOT).unwrap();
+        let manifests = BTreeMap::from([
+            (
+                "a/Cargo.toml",
+                "[package]\nname = \"a\"\n\n[dependencies]\n\
+                 serde = { workspace = true, features = [\"derive\"] }\n"
+                    .to_owned(),
+            ),
+            (
+                "b/Cargo.toml",
+                "[package]\nname = \"b\"\n\n[dependencies]\nserde.workspace = true\n".to_owned(),
+            ),
+        ]);
+
+        let fixed = fix_manifests(&manifests, &ws_info(&root)).unwrap();
+
+        assert_eq!(
+            fixed,
+            BTreeMap::from([(
+                "a/Cargo.toml",
+                "[package]\nname = \"a\"\n\n[dependencies]\nserde.workspace = true\n".to_owned(),
+            )])
+        );
+    }
+
+    #[test]
+    fn identical_workspace_features_collapse_to_dotted() {
+        assert_eq!(
+            fixed_member(
+                "[package]\nname = \"a\"\n\n[dependencies]\n\
+                 tokio = { workspace = true, features = [\"rt\", \"macros\"] }\n"
+            )
+            .as_deref(),
+            Some("[package]\nname = \"a\"\n\n[dependencies]\ntokio.workspace = true\n")
+        );
+        assert_eq!(
+            fixed_member(
+                "[package]\nname = \"a\"\n\n[dependencies]\n\
+                 tokio = { workspace = true, features = [\"fs\", \"rt\"] }\n"
+            )
+            .as_deref(),
+            Some(
+                "[package]\nname = \"a\"\n\n[dependencies]\n\
+                 tokio = { workspace = true, features = [\"fs\"] }\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Dependency table ordering policy.
+use std::cmp::Ordering;
+
+/// Orders keys by the first matching prefix group, then alphabetically.
+/// Keys matching no prefix sort last.
+pub struct DependencyOrder<'a> {
+    pub prefixes: &'a [String],
+}
+
+impl DependencyOrder<'_> {
+    fn group(&self, key: &str) -> usize {
+        self.prefixes
+            .iter()
+            .position(|p| key.starts_with(p.as_str()))
+            .unwrap_or(self.prefixes.len())
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.group(a).cmp(&self.group(b)).then_with(|| a.cmp(b))
+    }
+
+    /// Sort the key-value entries of a dependency table, subtables are unaffected.
+    ///
+    /// Returns whether the table was out of order.
+    pub fn sort(&self, item: &mut toml_edit::Item) -> bool {
+        if self.is_sorted(item) {
+            return false;
+        }
+        match item {
+            toml_edit::Item::Table(t) => {
+                t.sort_values_by(|a, _, b, _| self.compare(a.get(), b.get()))
+            }
+            toml_edit::Item::Value(toml_edit::Value::InlineTable(t)) => {
+                t.sort_values_by(|a, _, b, _| self.compare(a.get(), b.get()))
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn is_sorted(&self, item: &toml_edit::Item) -> bool {
+        let Some(table) = item.as_table_like() else {
+            return true;
+        };
+        let keys: Vec<_> = table
+            .iter()
+            .filter(|(_, v)| v.is_value())
+            .map(|(k, _)| k)
+            .collect();
+        keys.windows(2)
+            .all(|w| self.compare(w[0], w[1]) != Ordering::Greater)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_groups_first() {
+        let prefixes = ["my-".to_owned()];
+        let order = DependencyOrder {
+            prefixes: &prefixes,
+        };
+        assert_eq!(order.compare("my-b", "anyhow"), Ordering::Less);
+        assert_eq!(order.compare("my-b", "my-a"), Ordering::Greater);
+        assert_eq!(order.compare("anyhow", "serde"), Ordering::Less);
+    }
+
+    #[test]
+    fn sort_table() {
+        let prefixes = ["my-".to_owned()];
+        let order = DependencyOrder {
+            prefixes: &prefixes,
+        };
+        let mut doc: toml_edit::DocumentMut =
+            "[dependencies]\nserde = \"1\"\nmy-a = \"1\"\nanyhow = \"1\"\n"
+                .parse()
+                .unwrap();
+        assert!(order.sort(&mut doc["dependencies"]));
+        assert_eq!(
+            doc.to_string(),
+            "[dependencies]\nmy-a = \"1\"\nanyhow = \"1\"\nserde = \"1\"\n"
+        );
+        assert!(!order.sort(&mut doc["dependencies"]));
+    }
+}
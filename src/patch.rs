@@ -0,0 +1,132 @@
+//! `--patch-out` unified diff patches applyable with `git apply`.
+
+/// Lines of context around each hunk.
+const CONTEXT: usize = 3;
+
+/// Unified diff of a file at workspace relative `path`, empty if unchanged.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<_> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<_> = new.split_inclusive('\n').collect();
+    let ops = diff::slice(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, diff::Result::Both(..)))
+        .map(|(idx, _)| idx)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // op index ranges of each hunk, merging changes with overlapping context
+    let mut hunks: Vec<(usize, usize)> = vec![];
+    for idx in changes {
+        let start = idx.saturating_sub(CONTEXT);
+        let end = (idx + 1 + CONTEXT).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n");
+    let (mut old_line, mut new_line, mut op_idx) = (0, 0, 0);
+    for (start, end) in hunks {
+        for op in &ops[op_idx..start] {
+            advance(op, &mut old_line, &mut new_line);
+        }
+        let (old_start, new_start) = (old_line, new_line);
+        let mut body = String::new();
+        for op in &ops[start..end] {
+            advance(op, &mut old_line, &mut new_line);
+            let (prefix, line) = match op {
+                diff::Result::Both(line, _) => (' ', line),
+                diff::Result::Left(line) => ('-', line),
+                diff::Result::Right(line) => ('+', line),
+            };
+            body.push(prefix);
+            body.push_str(line);
+            if !line.ends_with('\n') {
+                body.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        op_idx = end;
+
+        out += &format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_line - old_start),
+            range(new_start, new_line - new_start),
+        );
+        out += &body;
+    }
+    out
+}
+
+fn advance(op: &diff::Result<&&str>, old_line: &mut usize, new_line: &mut usize) {
+    match op {
+        diff::Result::Both(..) => {
+            *old_line += 1;
+            *new_line += 1;
+        }
+        diff::Result::Left(_) => *old_line += 1,
+        diff::Result::Right(_) => *new_line += 1,
+    }
+}
+
+/// Hunk range `start,len` where `lines_before` precede the hunk.
+fn range(lines_before: usize, len: usize) -> String {
+    match len {
+        0 => format!("{lines_before},0"),
+        1 => format!("{}", lines_before + 1),
+        _ => format!("{},{len}", lines_before + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchanged() {
+        assert_eq!(unified_diff("Cargo.toml", "a\n", "a\n"), "");
+    }
+
+    #[test]
+    fn single_hunk() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+        assert_eq!(
+            unified_diff("a/Cargo.toml", old, new),
+            "diff --git a/a/Cargo.toml b/a/Cargo.toml\n\
+             --- a/a/Cargo.toml\n\
+             +++ b/a/Cargo.toml\n\
+             @@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+        );
+    }
+
+    #[test]
+    fn separate_hunks() {
+        let old: String = (1..=20).map(|n| format!("{n}\n")).collect();
+        let new = old.replacen("2\n", "", 1).replace("19\n", "19\nnew\n");
+        assert_eq!(
+            unified_diff("Cargo.toml", &old, &new),
+            "diff --git a/Cargo.toml b/Cargo.toml\n\
+             --- a/Cargo.toml\n\
+             +++ b/Cargo.toml\n\
+             @@ -1,5 +1,4 @@\n 1\n-2\n 3\n 4\n 5\n\
+             @@ -17,4 +16,5 @@\n 17\n 18\n 19\n+new\n 20\n"
+        );
+    }
+
+    #[test]
+    fn no_final_newline() {
+        assert_eq!(
+            unified_diff("Cargo.toml", "a", "a\n"),
+            "diff --git a/Cargo.toml b/Cargo.toml\n\
+             --- a/Cargo.toml\n\
+             +++ b/Cargo.toml\n\
+             @@ -1 +1 @@\n-a\n\\ No newline at end of file\n+a\n"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Manifest problems `cargo metadata` rejects, checked when it fails so they're reported
+//! as findings rather than a raw cargo error.
+use crate::{config::Config, finding::Finding, normalize_path, report::Reporter};
+use anyhow::Context;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use cargo_toml::Manifest;
+use fs_err as fs;
+
+/// Report problems cargo rejects in the workspace manifests of `dir`, in the configured format.
+///
+/// Returns whether any were found, having printed the final status.
+pub fn report(dir: &Utf8Path) -> anyhow::Result<bool> {
+    let Some(root) = workspace_root(dir) else {
+        return Ok(false);
+    };
+    let root_toml = root.join("Cargo.toml");
+    let root_toml_str = fs::read_to_string(&root_toml)?;
+    let root_doc = root_toml_str.parse::<toml_edit::DocumentMut>().ok();
+    // user config still applies if the root manifest doesn't parse
+    let config = Config::load(root_doc.as_ref().unwrap_or(&toml_edit::DocumentMut::new()))
+        .with_context(|| format!("{root_toml}"))?;
+    let mut reporter = Reporter::new(crate::output_format(&config)?);
+
+    let mut manifests = vec![root_toml.clone()];
+    for member in root_doc.iter().flat_map(|doc| member_manifests(&root, doc)) {
+        if !manifests.contains(&member) {
+            manifests.push(member);
+        }
+    }
+
+    // cargo can't load the workspace with missing members, lint the others' manifests instead
+    let missing_members = manifests.iter().any(|p| !p.is_file());
+    if missing_members {
+        reporter.note("Member manifests missing, only linting the other members' manifests");
+    }
+    let root_manifest = missing_members
+        .then(|| Manifest::from_str(&root_toml_str).ok())
+        .flatten();
+
+    for path in &manifests {
+        let display_path = path.strip_prefix(&root).unwrap_or(path);
+        if !path.is_file() {
+            let finding = Finding::new(
+                crate::MISSING_MEMBER_MANIFEST,
+                "Member manifest not found (submodule not initialized?)",
+            );
+            reporter.checking(display_path);
+            reporter.report(display_path, "", vec![finding]);
+            continue;
+        }
+        log::debug!("checking {display_path} for problems cargo rejects");
+        let toml = fs::read_to_string(path)?;
+        let Ok(doc) = toml.parse::<toml_edit::DocumentMut>() else {
+            continue; // cargo's toml errors are clear enough
+        };
+        let mut findings = check(&toml, &doc);
+        if let Some(root_manifest) = &root_manifest {
+            if findings.is_empty() && doc.contains_key("package") {
+                findings = lint_member(&toml, doc, path == &root_toml, root_manifest, &config);
+            }
+        }
+        if !findings.is_empty() {
+            reporter.checking(display_path);
+            reporter.report(display_path, &toml, findings);
+        }
+    }
+    let issues = reporter.has_issues();
+    if issues {
+        reporter.finish(false);
+    }
+    Ok(issues)
+}
+
+fn check(toml: &str, doc: &toml_edit::DocumentMut) -> Vec<Finding> {
+    let mut findings = crate::dependency_arrays_of_tables(doc);
+    if !findings.is_empty() {
+        return findings;
+    }
+    // cargo rejects these padded, reported without a fix as nothing is fixed here
+    let mut padded = vec![];
+    let mut padded_doc = doc.clone();
+    crate::padded_package_fields(&mut padded_doc, &["package"], &mut padded);
+    crate::padded_package_fields(&mut padded_doc, &["workspace", "package"], &mut padded);
+    let rejected_by_cargo = |f: &Finding| {
+        let keys: Vec<_> = f.keys.iter().map(String::as_str).collect();
+        matches!(
+            keys[..],
+            ["package", "version" | "edition"] | ["workspace", "package", "version"]
+        )
+    };
+    findings.extend(
+        padded
+            .into_iter()
+            .filter(rejected_by_cargo)
+            .map(|f| Finding {
+                fixable: false,
+                ..f
+            }),
+    );
+    let Ok(manifest) = Manifest::from_str(toml) else {
+        return findings;
+    };
+    crate::non_optional_dep_features(&manifest, &mut findings);
+    for table in crate::dependency_tables(&manifest) {
+        crate::conflicting_git_refs(&table, &mut findings);
+    }
+    if let Some(ws) = &manifest.workspace {
+        let table = crate::DepTable {
+            deps: &ws.dependencies,
+            keys: vec!["workspace", "dependencies"],
+            item_name: "workspace dependency",
+        };
+        crate::conflicting_git_refs(&table, &mut findings);
+    }
+    findings
+}
+
+/// Lints of the member manifest needing only its contents, see [`crate::lint_member_doc`].
+///
+/// Not fixable, as `--fix` needs cargo to load the workspace.
+fn lint_member(
+    toml: &str,
+    mut doc: toml_edit::DocumentMut,
+    is_root: bool,
+    root: &Manifest,
+    config: &Config,
+) -> Vec<Finding> {
+    let Ok(member) = Manifest::from_str(toml) else {
+        return vec![];
+    };
+    let include_dev = crate::cli_include_dev()
+        .or(config.dev_dependencies)
+        .unwrap_or(true);
+    let member = match include_dev {
+        true => member,
+        false => crate::without_dev_dependencies(&member),
+    };
+    let ws = crate::WorkspaceInfo {
+        root,
+        misnamed_deps: &[],
+        dependency_order: config.dependency_order.as_deref(),
+        include_dev,
+        check_whitespace: std::env::args().any(|a| a == "--check-whitespace"),
+    };
+    crate::lint_member_doc(toml, &member, &mut doc, is_root, &ws)
+        .into_iter()
+        .map(|f| Finding {
+            fixable: false,
+            ..f
+        })
+        .collect()
+}
+
+/// The nearest ancestor directory with a `[workspace]` manifest,
+/// otherwise the nearest with any manifest, like cargo.
+fn workspace_root(dir: &Utf8Path) -> Option<Utf8PathBuf> {
+    let manifests: Vec<_> = dir
+        .ancestors()
+        .map(|d| d.join("Cargo.toml"))
+        .filter(|p| p.is_file())
+        .collect();
+    let is_workspace = |path: &Utf8PathBuf| {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|toml| toml.parse::<toml_edit::DocumentMut>().ok())
+            .is_some_and(|doc| doc.contains_key("workspace"))
+    };
+    let root = manifests
+        .iter()
+        .find(|p| is_workspace(p))
+        .or(manifests.first())?;
+    root.parent().map(Utf8Path::to_path_buf)
+}
+
+/// Manifest paths of the `[workspace] members`, expanding globs, except those `exclude`d.
+/// Paths may not exist.
+fn member_manifests(root: &Utf8Path, doc: &toml_edit::DocumentMut) -> Vec<Utf8PathBuf> {
+    let strings = |key: &str| {
+        doc.get("workspace")
+            .and_then(|ws| ws.get(key))
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_owned())
+            .collect::<Vec<_>>()
+    };
+    let excluded: Vec<_> = strings("exclude")
+        .iter()
+        .map(|e| normalize_path(&root.join(e)))
+        .collect();
+
+    let mut manifests = vec![];
+    for member in strings("members") {
+        let pattern = root.join(&member);
+        let dirs = match member.contains(['*', '?', '[']) {
+            true => glob::glob(pattern.as_str())
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .filter(|p| p.is_dir())
+                .filter_map(|p| Utf8PathBuf::from_path_buf(p).ok())
+                .collect(),
+            false => vec![pattern],
+        };
+        for dir in dirs.iter().map(|d| normalize_path(d)) {
+            if !excluded.iter().any(|e| dir.starts_with(e)) {
+                manifests.push(dir.join("Cargo.toml"));
+            }
+        }
+    }
+    manifests
+}
@@ -0,0 +1,388 @@
+//! Output of findings & fixes.
+use crate::finding::{self, Finding, Severity};
+use cargo_metadata::camino::Utf8Path;
+use colored::Colorize;
+use serde::Serialize;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Colored output with per-manifest headers.
+    Human,
+    /// Single line per finding `path:line: [lint-id] message`.
+    HumanCompact,
+    /// Yaml list of all findings printed to stdout when finished.
+    Yaml,
+    /// Number of findings affecting the exit code printed to stdout when finished.
+    Count,
+    /// GitLab code quality report json printed to stdout when finished.
+    GitlabCodequality,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "human-compact" => Ok(Self::HumanCompact),
+            "yaml" => Ok(Self::Yaml),
+            "count" => Ok(Self::Count),
+            "gitlab-codequality" => Ok(Self::GitlabCodequality),
+            _ => anyhow::bail!(
+                "unknown format `{s}`, expected human|human-compact|yaml|count|gitlab-codequality"
+            ),
+        }
+    }
+}
+
+pub struct Reporter {
+    pub format: Format,
+    /// All reported findings.
+    pub findings: Vec<Finding>,
+    /// Ignore findings `--fix` can't resolve.
+    pub only_fixable: bool,
+    /// Ignore findings of other lints.
+    pub only: Option<String>,
+}
+
+impl Reporter {
+    pub fn new(format: Format) -> Self {
+        if format != Format::Human {
+            colored::control::set_override(false);
+        }
+        Self {
+            format,
+            findings: vec![],
+            only_fixable: false,
+            only: None,
+        }
+    }
+
+    /// "==> Checking ..." header.
+    pub fn checking(&self, what: impl std::fmt::Display) {
+        if self.format == Format::Human {
+            eprintln!("==> Checking {what}");
+        }
+    }
+
+    /// Dimmed informational line, e.g. explaining skipped lints.
+    pub fn note(&self, note: &str) {
+        log::info!("{note}");
+        if self.format == Format::Human {
+            eprintln!("{}", note.dimmed());
+        }
+    }
+
+    /// Report findings of the manifest at `path` with contents `toml`.
+    pub fn report(&mut self, path: &Utf8Path, toml: &str, findings: Vec<Finding>) {
+        if findings.is_empty() {
+            return;
+        }
+        let doc = toml_edit::ImDocument::parse(toml).ok();
+
+        for mut f in findings {
+            if self.only_fixable && !f.fixable {
+                continue;
+            }
+            if self.only.as_deref().is_some_and(|only| only != f.lint) {
+                continue;
+            }
+            f.path = Some(path.to_string());
+            if f.line.is_none() {
+                f.line = doc.as_ref().and_then(|doc| finding::line_of(doc, &f.keys));
+            }
+            log::debug!(
+                "{path}:{}: {} fixable={}",
+                f.line.unwrap_or(0),
+                f.lint,
+                f.fixable
+            );
+            match self.format {
+                Format::Human if f.severity == Severity::Error => {
+                    eprintln!("{}", f.message.red())
+                }
+                Format::Human => eprintln!("{}", f.message.yellow()),
+                Format::HumanCompact => match f.line {
+                    Some(line) => println!("{path}:{line}: [{}] {}", f.lint, f.message),
+                    None => println!("{path}: [{}] {}", f.lint, f.message),
+                },
+                Format::Yaml | Format::Count | Format::GitlabCodequality => {}
+            }
+            self.findings.push(f);
+        }
+    }
+
+    /// Print a fix summary for the file, attributing the `fixes` applied,
+    /// the full diff only if `show_diff`.
+    pub fn fix(
+        &self,
+        path: &Utf8Path,
+        old: &str,
+        new: &str,
+        fixes: &[Finding],
+        dry_run: bool,
+        show_diff: bool,
+    ) {
+        let (mut added, mut removed) = (0, 0);
+        for diff in diff::lines(old, new) {
+            match diff {
+                diff::Result::Left(_) => removed += 1,
+                diff::Result::Right(_) => added += 1,
+                _ => {}
+            }
+        }
+        eprintln!(
+            "{} {} {}{} {}",
+            match dry_run {
+                true => "Would fix",
+                false => "Fixed",
+            },
+            path.as_str().bold(),
+            format!("+{added}").green(),
+            format!(" -{removed}").red(),
+            format!("({})", fixed_lints(fixes).join(", ")).dimmed(),
+        );
+        if let Some(summary) = removed_features_summary(fixes) {
+            eprintln!("  {}", summary.dimmed());
+        }
+        if show_diff {
+            print_diff(old, new);
+        }
+    }
+
+    /// Whether any reported findings affect the exit code.
+    pub fn has_issues(&self) -> bool {
+        self.findings.iter().any(|f| f.severity > Severity::Info)
+    }
+
+    /// Print the final status & return whether the run should fail.
+    pub fn finish(&self, fix: bool) -> bool {
+        match self.format {
+            Format::Yaml => print!("{}", yaml(&self.findings)),
+            Format::Count => {
+                let relevant = self.findings.iter().filter(|f| f.severity > Severity::Info);
+                println!("{}", relevant.count());
+            }
+            Format::GitlabCodequality => {
+                let issues: Vec<_> = self.findings.iter().map(CodeQualityIssue::from).collect();
+                match serde_json::to_string_pretty(&issues) {
+                    Ok(json) => println!("{json}"),
+                    Err(err) => log::error!("failed to serialize findings: {err}"),
+                }
+            }
+            Format::Human | Format::HumanCompact => {}
+        }
+
+        let relevant = || self.findings.iter().filter(|f| f.severity > Severity::Info);
+        let something_to_fix = relevant().any(|f| f.fixable);
+        let something_to_review = relevant().any(|f| !f.fixable);
+
+        if !fix && something_to_fix {
+            if self.format == Format::Human {
+                eprintln!(
+                    "{}{}",
+                    "Hint: To fix run with ".dimmed(),
+                    "--fix".dimmed().bold()
+                );
+            }
+            return true;
+        }
+        if something_to_review {
+            return true;
+        }
+
+        if self.format == Format::Human {
+            eprintln!("{}", "All good ✔".green());
+        }
+        false
+    }
+}
+
+/// Ids of fixable lints in `findings`.
+pub fn fixed_lints(findings: &[Finding]) -> Vec<&'static str> {
+    let mut lints = vec![];
+    for f in findings.iter().filter(|f| f.fixable) {
+        if !lints.contains(&f.lint) {
+            lints.push(f.lint);
+        }
+    }
+    lints
+}
+
+/// Single line summary of all dependency features removed by `fixes`, e.g.
+/// `Removed redundant features ["derive", "std"] from deps ["serde", "tokio"]`.
+fn removed_features_summary(fixes: &[Finding]) -> Option<String> {
+    let (mut features, mut deps) = (vec![], vec![]);
+    for f in fixes.iter().filter(|f| !f.removed_features.is_empty()) {
+        for feature in &f.removed_features {
+            if !features.contains(&feature) {
+                features.push(feature);
+            }
+        }
+        if let Some(dep) = f.keys.last().filter(|dep| !deps.contains(dep)) {
+            deps.push(dep);
+        }
+    }
+    if features.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Removed redundant features {features:?} from deps {deps:?}"
+    ))
+}
+
+/// Yaml list of the findings, strings double quoted.
+fn yaml(findings: &[Finding]) -> String {
+    if findings.is_empty() {
+        return "[]\n".into();
+    }
+    let mut out = String::new();
+    for f in findings {
+        let mut lines = vec![];
+        if let Some(path) = &f.path {
+            lines.push(format!("path: {}", yaml_str(path)));
+        }
+        lines.push(format!("lint: {}", yaml_str(f.lint)));
+        lines.push(format!("message: {}", yaml_str(&f.message)));
+        let severity = match f.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        lines.push(format!("severity: {severity}"));
+        lines.push(format!("fixable: {}", f.fixable));
+        match f.keys.is_empty() {
+            true => lines.push("keys: []".into()),
+            false => {
+                lines.push("keys:".into());
+                lines.extend(f.keys.iter().map(|k| format!("- {}", yaml_str(k))));
+            }
+        }
+        match f.line {
+            Some(line) => lines.push(format!("line: {line}")),
+            None => lines.push("line: null".into()),
+        }
+
+        for (idx, line) in lines.iter().enumerate() {
+            out += match idx {
+                0 => "- ",
+                _ => "  ",
+            };
+            out += line;
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Double quoted yaml string.
+fn yaml_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\t' => out += "\\t",
+            c if c.is_control() => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// GitLab code quality report entry.
+#[derive(Serialize)]
+struct CodeQualityIssue<'a> {
+    description: &'a str,
+    check_name: &'a str,
+    fingerprint: String,
+    severity: &'static str,
+    location: CodeQualityLocation<'a>,
+}
+
+#[derive(Serialize)]
+struct CodeQualityLocation<'a> {
+    path: &'a str,
+    lines: CodeQualityLines,
+}
+
+#[derive(Serialize)]
+struct CodeQualityLines {
+    begin: usize,
+}
+
+impl<'a> From<&'a Finding> for CodeQualityIssue<'a> {
+    fn from(f: &'a Finding) -> Self {
+        let path = f.path.as_deref().unwrap_or_default();
+        // stable across runs & line changes: lint, path & toml keys, e.g. the dependency.
+        // Findings without keys, e.g. trailing whitespace, use the message & line instead.
+        let line = f.line.map(|l| l.to_string()).unwrap_or_default();
+        let identity: Vec<_> = match f.keys.is_empty() {
+            true => vec![f.lint, path, &f.message, &line],
+            false => [f.lint, path]
+                .into_iter()
+                .chain(f.keys.iter().map(String::as_str))
+                .collect(),
+        };
+        Self {
+            description: &f.message,
+            check_name: f.lint,
+            fingerprint: format!("{:016x}", fnv1a(identity)),
+            severity: match f.severity {
+                Severity::Info => "info",
+                Severity::Warning => "minor",
+                Severity::Error => "major",
+            },
+            location: CodeQualityLocation {
+                path,
+                lines: CodeQualityLines {
+                    begin: f.line.unwrap_or(1),
+                },
+            },
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash of the `parts`, `\0` separated.
+fn fnv1a<'a>(parts: impl IntoIterator<Item = &'a str>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for b in part.bytes().chain([0]) {
+            hash ^= u64::from(b);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+fn print_diff(old: &str, new: &str) {
+    for diff in diff::lines(old, new) {
+        match diff {
+            diff::Result::Left(old) => eprintln!("{}{}", "-".red(), old.red()),
+            diff::Result::Right(new) => eprintln!("{}{}", "+".green(), new.green()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyless_fingerprints_differ() {
+        let finding = |line| Finding {
+            path: Some("Cargo.toml".into()),
+            ..Finding::new("whitespace", "Trailing whitespace").line(line)
+        };
+        let (a, b) = (finding(2), finding(5));
+        assert_ne!(
+            CodeQualityIssue::from(&a).fingerprint,
+            CodeQualityIssue::from(&b).fingerprint
+        );
+    }
+}
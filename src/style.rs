@@ -0,0 +1,325 @@
+//! Formatting style detection so fixed manifests keep the formatting of the original.
+
+/// Prevailing formatting style of a toml document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TomlStyle {
+    /// Leading whitespace unit used for indented lines, e.g. in multi-line arrays.
+    pub indent: Indent,
+    /// `key=val` rather than `key = val`.
+    pub compact_eq: bool,
+    /// `\r\n` rather than `\n` line endings.
+    pub crlf: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    Tabs,
+    Spaces,
+}
+
+impl TomlStyle {
+    /// Detect the style from the majority of key-value & indented lines.
+    pub fn detect(toml: &str) -> Self {
+        let (mut tabs, mut spaces) = (0, 0);
+        let (mut compact, mut spaced) = (0, 0);
+
+        for line in toml.lines() {
+            if line.starts_with('\t') {
+                tabs += 1;
+            } else if line.starts_with(' ') {
+                spaces += 1;
+            }
+
+            let line = line.trim_start();
+            if line.starts_with(['[', '#', '"', '\'']) {
+                continue;
+            }
+            if let Some(idx) = line.find('=') {
+                match line[..idx].ends_with(' ') || line[idx + 1..].starts_with(' ') {
+                    true => spaced += 1,
+                    false => compact += 1,
+                }
+            }
+        }
+
+        Self {
+            indent: match tabs > spaces {
+                true => Indent::Tabs,
+                false => Indent::Spaces,
+            },
+            compact_eq: compact > spaced,
+            crlf: toml.contains("\r\n"),
+        }
+    }
+
+    fn eq(&self) -> &'static str {
+        match self.compact_eq {
+            true => "=",
+            false => " = ",
+        }
+    }
+
+    /// Format the fixed document consistently with the original toml.
+    ///
+    /// * Collapses `foo = { workspace = true }` into `foo.workspace = true`.
+    /// * Re-indents new/changed lines to the original indentation style.
+    /// * Uses compact `key=val` in new/changed lines if the original does.
+    /// * Uses the original line endings.
+    pub fn format_fixed(&self, original: &str, fixed: &str) -> String {
+        let original_lines: std::collections::HashSet<_> = original.lines().collect();
+        let mut out = String::with_capacity(fixed.len());
+        let mut in_multiline_str = false;
+
+        for line in fixed.split_inclusive('\n') {
+            let content = line.trim_end_matches(['\r', '\n']);
+            let eol = match (line.ends_with('\n'), self.crlf) {
+                (false, _) => "",
+                (true, true) => "\r\n",
+                (true, false) => "\n",
+            };
+            let was_in_multiline_str = in_multiline_str;
+            in_multiline_str ^= toggles_multiline_str(content);
+            if was_in_multiline_str || original_lines.contains(content) {
+                out.push_str(content);
+                out.push_str(eol);
+                continue;
+            }
+
+            let trimmed = content.trim_start();
+            let indent = &content[..content.len() - trimmed.len()];
+            match self.indent {
+                Indent::Tabs => out.push_str(&indent.replace("    ", "\t")),
+                Indent::Spaces => out.push_str(&indent.replace('\t', "    ")),
+            }
+            match collapse_workspace_true(trimmed) {
+                Some(key) => {
+                    out.push_str(key);
+                    out.push_str(".workspace");
+                    out.push_str(self.eq());
+                    out.push_str("true");
+                }
+                None if self.compact_eq && !in_multiline_str => out.push_str(&compact_eqs(trimmed)),
+                None => out.push_str(trimmed),
+            }
+            out.push_str(eol);
+        }
+        out
+    }
+}
+
+/// `key = val` separators outside of strings & comments as `key=val`.
+fn compact_eqs(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut quote = None;
+    let mut chars = line.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match (quote, c) {
+            (Some('"'), '\\') => {
+                out.push(c);
+                if let Some((_, escaped)) = chars.next() {
+                    out.push(escaped);
+                }
+                continue;
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => {
+                out.push_str(&line[idx..]);
+                break;
+            }
+            (None, '=') => {
+                out.truncate(out.trim_end_matches(' ').len());
+                out.push('=');
+                while chars.next_if(|(_, c)| *c == ' ').is_some() {}
+                continue;
+            }
+            (None, _) => {}
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Returns the key if the line is `key = { workspace = true }` with any spacing.
+fn collapse_workspace_true(line: &str) -> Option<&str> {
+    let (key, value) = line.split_once('=')?;
+    let key = key.trim_end();
+    let inner = value
+        .trim()
+        .strip_prefix('{')?
+        .strip_suffix('}')?
+        .trim()
+        .strip_prefix("workspace")?
+        .trim_start()
+        .strip_prefix('=')?
+        .trim();
+    (inner == "true" && !key.is_empty()).then_some(key)
+}
+
+/// 1-based line numbers with trailing spaces/tabs, ignoring lines inside multi-line strings.
+pub fn trailing_whitespace_lines(toml: &str) -> Vec<usize> {
+    let mut in_multiline_str = false;
+    let mut lines = vec![];
+    for (idx, line) in toml.lines().enumerate() {
+        let was_in_multiline_str = in_multiline_str;
+        in_multiline_str ^= toggles_multiline_str(line);
+        if !was_in_multiline_str && !in_multiline_str && line.ends_with([' ', '\t']) {
+            lines.push(idx + 1);
+        }
+    }
+    lines
+}
+
+/// Trim trailing whitespace on lines & ensure exactly one final newline.
+///
+/// Preserves CRLF line endings & leaves multi-line string content untouched.
+pub fn trim_whitespace(toml: &str) -> String {
+    let eol = match toml.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    };
+    let mut in_multiline_str = false;
+    let mut out = String::with_capacity(toml.len());
+
+    for line in toml.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let line_eol = &line[content.len()..];
+        let was_in_multiline_str = in_multiline_str;
+        in_multiline_str ^= toggles_multiline_str(content);
+        match was_in_multiline_str || in_multiline_str {
+            true => out.push_str(content),
+            false => out.push_str(content.trim_end_matches([' ', '\t'])),
+        }
+        out.push_str(line_eol);
+    }
+
+    if in_multiline_str {
+        // unbalanced quotes, don't risk touching content
+        return out;
+    }
+
+    let content_len = out.trim_end_matches(['\r', '\n']).len();
+    if content_len == 0 {
+        return String::new();
+    }
+    out.truncate(content_len);
+    out.push_str(eol);
+    out
+}
+
+/// Whether the line opens/closes a `"""` or `'''` multi-line string.
+fn toggles_multiline_str(line: &str) -> bool {
+    (line.matches("\"\"\"").count() + line.matches("'''").count()) % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_tabs_compact() {
+        let toml =
+            "[package]\nname=\"a\"\nversion=\"0.1.0\"\n\n[features]\nx=[\n\t\"y\",\n\t\"z\",\n]\n";
+        let style = TomlStyle::detect(toml);
+        assert_eq!(style.indent, Indent::Tabs);
+        assert!(style.compact_eq);
+        assert!(!style.crlf);
+    }
+
+    #[test]
+    fn detect_spaces_spaced() {
+        let toml = "[package]\nname = \"a\"\n\n[features]\nx = [\n    \"y\",\n]\n";
+        let style = TomlStyle::detect(toml);
+        assert_eq!(style.indent, Indent::Spaces);
+        assert!(!style.compact_eq);
+    }
+
+    #[test]
+    fn format_fixed_tabs() {
+        let original = "[features]\nx = [\n\t\"a\",\n\t\"b\",\n\t\"c\",\n]\n";
+        let fixed = "[features]\nx = [\n    \"a\",\n    \"c\",\n]\n";
+        assert_eq!(
+            TomlStyle::detect(original).format_fixed(original, fixed),
+            "[features]\nx = [\n\t\"a\",\n\t\"c\",\n]\n"
+        );
+    }
+
+    #[test]
+    fn format_fixed_compact() {
+        let original = "[dependencies]\nfoo={workspace=true,features=[\"a\"]}\nbar={version=\"1\",optional=false}\n";
+        let fixed = "[dependencies]\nfoo={ workspace = true }\nbar={ version = \"1\" }\n";
+        assert_eq!(
+            TomlStyle::detect(original).format_fixed(original, fixed),
+            "[dependencies]\nfoo.workspace=true\nbar={ version=\"1\" }\n"
+        );
+    }
+
+    #[test]
+    fn format_fixed_compact_leaves_strings_comments() {
+        let original = "a=1\nb=2\n";
+        let fixed = "a = \"x = y\" # c = d\nb=2\n";
+        assert_eq!(
+            TomlStyle::detect(original).format_fixed(original, fixed),
+            "a=\"x = y\" # c = d\nb=2\n"
+        );
+    }
+
+    #[test]
+    fn format_fixed_spaced() {
+        let original = "[dependencies]\nfoo = { workspace = true, features = [\"a\"] }\n";
+        let fixed = "[dependencies]\nfoo = { workspace = true }\n";
+        assert_eq!(
+            TomlStyle::detect(original).format_fixed(original, fixed),
+            "[dependencies]\nfoo.workspace = true\n"
+        );
+    }
+
+    #[test]
+    fn format_fixed_crlf() {
+        let original =
+            "[dependencies]\r\nfoo = { workspace = true, features = [\"a\"] }\r\nbar = \"1\"\r\n";
+        let fixed = "[dependencies]\r\nfoo = { workspace = true }\nbar = \"1\"\r\n";
+        assert_eq!(
+            TomlStyle::detect(original).format_fixed(original, fixed),
+            "[dependencies]\r\nfoo.workspace = true\r\nbar = \"1\"\r\n"
+        );
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        let toml = "a = 1 \nb = \"\"\"\nkept  \n\"\"\"\nc = 2\t\n";
+        assert_eq!(trailing_whitespace_lines(toml), [1, 5]);
+    }
+
+    #[test]
+    fn trim_whitespace_only_touches_whitespace() {
+        let toml = "a = 1  \nb = \"\"\"\nkept  \n\"\"\"\n\n\n";
+        assert_eq!(trim_whitespace(toml), "a = 1\nb = \"\"\"\nkept  \n\"\"\"\n");
+        assert_eq!(trim_whitespace("a = 1"), "a = 1\n");
+        assert_eq!(trim_whitespace("\n\n"), "");
+    }
+
+    #[test]
+    fn trim_whitespace_crlf() {
+        assert_eq!(
+            trim_whitespace("a = 1 \r\nb = 2\r\n\r\n"),
+            "a = 1\r\nb = 2\r\n"
+        );
+    }
+
+    #[test]
+    fn trim_whitespace_idempotent() {
+        for toml in [
+            "a = 1  \nb = 2\t\n\n\n",
+            "a = 1 \r\nb = 2\r\n\r\n",
+            "a = 1",
+            "b = \"\"\"\nkept  \n\"\"\"  \n",
+            "\n",
+        ] {
+            let once = trim_whitespace(toml);
+            assert_eq!(trim_whitespace(&once), once, "{toml:?}");
+            assert!(trailing_whitespace_lines(&once).is_empty(), "{toml:?}");
+        }
+    }
+}
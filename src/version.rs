@@ -0,0 +1,153 @@
+//! Version requirement analysis.
+use semver::{Comparator, Op, VersionReq};
+
+/// `major.minor.patch`, ignoring pre-releases.
+type V = (u64, u64, u64);
+
+/// Whether no version can satisfy all the comparators, e.g. `>=2, <1`.
+pub fn is_unsatisfiable(req: &VersionReq) -> bool {
+    let mut lo: V = (0, 0, 0);
+    let mut hi: Option<V> = None;
+
+    for cmp in &req.comparators {
+        let (c_lo, c_hi) = range(cmp);
+        lo = lo.max(c_lo);
+        hi = match (hi, c_hi) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    hi.is_some_and(|hi| lo >= hi)
+}
+
+/// Half-open range `[lo, hi)` of versions matching the comparator.
+fn range(cmp: &Comparator) -> (V, Option<V>) {
+    let major = cmp.major;
+    let (minor, patch) = (cmp.minor, cmp.patch);
+    let min = (major, minor.unwrap_or(0), patch.unwrap_or(0));
+    // next version after all versions matching the partial version
+    let after_partial = match (minor, patch) {
+        (Some(minor), Some(patch)) => (major, minor, patch + 1),
+        (Some(minor), None) => (major, minor + 1, 0),
+        _ => (major + 1, 0, 0),
+    };
+
+    match cmp.op {
+        Op::Exact | Op::Wildcard => (min, Some(after_partial)),
+        Op::Greater => (after_partial, None),
+        Op::GreaterEq => (min, None),
+        Op::Less => ((0, 0, 0), Some(min)),
+        Op::LessEq => ((0, 0, 0), Some(after_partial)),
+        Op::Tilde => match minor {
+            Some(minor) => (min, Some((major, minor + 1, 0))),
+            None => (min, Some((major + 1, 0, 0))),
+        },
+        Op::Caret => match (major, minor, patch) {
+            (0, Some(0), Some(patch)) => (min, Some((0, 0, patch + 1))),
+            (0, Some(minor), _) => (min, Some((0, minor + 1, 0))),
+            _ => (min, Some((major + 1, 0, 0))),
+        },
+        _ => ((0, 0, 0), None),
+    }
+}
+
+/// The loosest requirement matched by versions satisfying all of `reqs`, e.g.
+/// `"1.2.3"` for `"1"` and `"1.2.3"`.
+///
+/// `None` if no version satisfies all of them.
+pub fn common_req<'a>(reqs: impl IntoIterator<Item = &'a VersionReq>) -> Option<String> {
+    let mut lo: V = (0, 0, 0);
+    let mut hi: Option<V> = None;
+    for (c_lo, c_hi) in reqs.into_iter().flat_map(|r| &r.comparators).map(range) {
+        lo = lo.max(c_lo);
+        hi = match (hi, c_hi) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+    }
+    if hi.is_some_and(|hi| lo >= hi) {
+        return None;
+    }
+
+    let (major, minor, patch) = lo;
+    if lo == (0, 0, 0) && hi.is_none() {
+        return Some("*".into());
+    }
+    let caret_hi = match (major, minor) {
+        (0, 0) => (0, 0, patch + 1),
+        (0, _) => (0, minor + 1, 0),
+        _ => (major + 1, 0, 0),
+    };
+    let caret = match (major, minor, patch) {
+        (0, 0, _) => format!("0.0.{patch}"),
+        (_, _, 0) if major > 0 && minor == 0 => format!("{major}"),
+        (_, _, 0) => format!("{major}.{minor}"),
+        _ => format!("{major}.{minor}.{patch}"),
+    };
+    match hi {
+        Some(hi) if hi < caret_hi => Some(format!(
+            ">={major}.{minor}.{patch}, <{}.{}.{}",
+            hi.0, hi.1, hi.2
+        )),
+        _ => Some(caret),
+    }
+}
+
+/// Parse a `rust-version` like `"1.70"` or rustc version like `"1.80.1-nightly"`,
+/// missing components are 0 & pre-releases are ignored.
+pub fn parse_rust_version(v: &str) -> Option<(u64, u64, u64)> {
+    let v = v.split(['-', '+']).next()?;
+    let mut parts = v.split('.').map(|p| p.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().transpose().ok()?.unwrap_or(0);
+    let patch = parts.next().transpose().ok()?.unwrap_or(0);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> VersionReq {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn unsatisfiable() {
+        assert!(is_unsatisfiable(&req(">=2, <1")));
+        assert!(is_unsatisfiable(&req("^1.2, <1.2")));
+        assert!(is_unsatisfiable(&req("=1.0.0, >1.0.0")));
+        assert!(!is_unsatisfiable(&req(">=1, <2")));
+        assert!(!is_unsatisfiable(&req("~1.2, >=1.2.5")));
+        assert!(!is_unsatisfiable(&req("*")));
+    }
+
+    fn common(reqs: &[&str]) -> Option<String> {
+        let reqs: Vec<_> = reqs.iter().map(|r| req(r)).collect();
+        common_req(&reqs)
+    }
+
+    #[test]
+    fn common_reqs() {
+        assert_eq!(common(&["1", "1.2.3"]).as_deref(), Some("1.2.3"));
+        assert_eq!(common(&["1.2", "1"]).as_deref(), Some("1.2"));
+        assert_eq!(common(&["0.3", "0.3.1"]).as_deref(), Some("0.3.1"));
+        assert_eq!(common(&["1", "~1.2"]).as_deref(), Some(">=1.2.0, <1.3.0"));
+        assert_eq!(common(&["*"]).as_deref(), Some("*"));
+        assert_eq!(common(&["1", "2"]), None);
+    }
+
+    #[test]
+    fn rust_versions() {
+        assert_eq!(parse_rust_version("1.70"), Some((1, 70, 0)));
+        assert_eq!(parse_rust_version("1.80.1"), Some((1, 80, 1)));
+        assert_eq!(parse_rust_version("1.82.0-nightly"), Some((1, 82, 0)));
+        assert_eq!(parse_rust_version("1"), Some((1, 0, 0)));
+        assert_eq!(parse_rust_version("1.2.3.4"), None);
+        assert_eq!(parse_rust_version("one"), None);
+    }
+}
@@ -0,0 +1,380 @@
+//! Runs cargo-ab-lint against the workspaces in `tests/fixtures`.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A fresh copy of the fixture workspace `name`, so runs don't leave lock files
+/// or fixes behind.
+fn fixture(name: &str) -> PathBuf {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!(
+        "fixtures/{name}-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    copy_dir(&Path::new("tests/fixtures").join(name), &dir);
+    dir
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let to = to.join(entry.file_name());
+        match entry.file_type().unwrap().is_dir() {
+            true => copy_dir(&entry.path(), &to),
+            false => _ = fs::copy(entry.path(), to).unwrap(),
+        }
+    }
+}
+
+/// Run `cargo ab-lint` in `dir`.
+fn ab_lint(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cargo-ab-lint"))
+        .arg("ab-lint")
+        .args(args)
+        .current_dir(dir)
+        .env_remove("RUST_LOG")
+        .output()
+        .unwrap()
+}
+
+/// `--format human-compact` finding lines.
+fn findings(dir: &Path, args: &[&str]) -> Vec<String> {
+    let out = ab_lint(dir, &[&["--format", "human-compact"], args].concat());
+    String::from_utf8(out.stdout)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+/// Run `cargo ab-lint --no-deps --fix --allow-dirty` in `dir`, asserting success.
+fn fix(dir: &Path) -> Output {
+    let out = ab_lint(dir, &["--no-deps", "--fix", "--allow-dirty"]);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    out
+}
+
+fn read(dir: &Path, path: &str) -> String {
+    fs::read_to_string(dir.join(path)).unwrap()
+}
+
+#[test]
+fn root_package_should_inherit() {
+    let dir = fixture("root-package");
+    let out = ab_lint(&dir, &[]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains(
+            "Dependency util should inherit the workspace dependency, e.g. util.workspace = true"
+        ),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn empty_default_members() {
+    let dir = fixture("empty-default-members");
+    assert_eq!(
+        findings(&dir, &["--no-deps"]),
+        ["Cargo.toml:3: [empty-default-members] Empty default-members builds nothing by default, \
+          likely unintended"]
+    );
+
+    fix(&dir);
+    assert_eq!(read(&dir, "Cargo.toml"), "[workspace]\nmembers = [\"a\"]\n");
+}
+
+#[test]
+fn sourceless_workspace_deps() {
+    let dir = fixture("sourceless-workspace-dep");
+    let found = findings(&dir, &["--no-deps"]);
+    assert_eq!(
+        found
+            .iter()
+            .filter(|f| f.contains("[workspace-dep-without-source]"))
+            .collect::<Vec<_>>(),
+        [
+            "Cargo.toml:5: [workspace-dep-without-source] Workspace dependency anyhow has no \
+             version, git or path",
+            "Cargo.toml:6: [workspace-dep-without-source] Workspace dependency serde has no \
+             version, git or path",
+        ]
+    );
+
+    let out = ab_lint(&dir, &["--no-deps", "--format", "yaml"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("severity: error"));
+}
+
+#[test]
+fn ineffective_member_resolver() {
+    let dir = fixture("ineffective-resolver");
+    assert_eq!(
+        findings(&dir, &["--no-deps"]),
+        [
+            "a/Cargo.toml:5: [ineffective-resolver] Ineffective package.resolver in a non-root \
+          member, set it in the workspace root"
+        ]
+    );
+
+    fix(&dir);
+    assert_eq!(
+        read(&dir, "a/Cargo.toml"),
+        "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n"
+    );
+    assert!(read(&dir, "Cargo.toml").contains("resolver = \"2\""));
+}
+
+#[test]
+fn ineffective_member_resolver_unset_in_root() {
+    let dir = fixture("ineffective-resolver-unset-in-root");
+    assert_eq!(
+        findings(&dir, &["--no-deps"]),
+        [
+            "a/Cargo.toml:5: [ineffective-resolver] Ineffective package.resolver in a non-root \
+          member, set it in the workspace root"
+        ]
+    );
+
+    // cargo ignores it, so removing it doesn't change the resolver
+    fix(&dir);
+    assert_eq!(
+        read(&dir, "a/Cargo.toml"),
+        "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n"
+    );
+    assert_eq!(read(&dir, "Cargo.toml"), "[workspace]\nmembers = [\"a\"]\n");
+}
+
+#[test]
+fn member_workspace_resolver() {
+    // a member [workspace] table is another workspace root, which cargo rejects
+    let dir = fixture("member-workspace-resolver");
+    let out = ab_lint(&dir, &["--no-deps", "--format", "human-compact"]);
+    assert!(!out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("multiple workspace roots found in the same workspace"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn standalone_package() {
+    let dir = fixture("standalone");
+
+    let out = ab_lint(&dir, &["--no-deps"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("No [workspace] table, running package-level lints only"),
+        "{stderr}"
+    );
+
+    assert_eq!(
+        findings(&dir, &["--no-deps"]),
+        [
+            "Cargo.toml:5: [redundant-auto-discovery] Redundant autobins = true (default)",
+            "Cargo.toml:8: [redundant-optional-false] Redundant optional = false (default) \
+             for dependency anyhow",
+        ]
+    );
+
+    fix(&dir);
+    assert_eq!(
+        read(&dir, "Cargo.toml"),
+        "[package]\nname = \"standalone\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+         [dependencies]\nanyhow = { version = \"1\" }\n"
+    );
+}
+
+#[test]
+fn empty_dependency_features() {
+    let dir = fixture("empty-features");
+    assert_eq!(
+        findings(&dir, &["--no-deps"]),
+        [
+            "a/Cargo.toml:7: [empty-dependency-features] Redundant empty features = [] for \
+             dependency anyhow",
+            "Cargo.toml:5: [empty-dependency-features] Redundant empty features = [] for \
+             dependency serde",
+        ]
+    );
+
+    fix(&dir);
+    assert!(read(&dir, "a/Cargo.toml").contains("anyhow = { version = \"1\" }\n"));
+    assert!(read(&dir, "Cargo.toml").contains("serde = { version = \"1\" }\n"));
+}
+
+#[test]
+fn default_documentation_url() {
+    let dir = fixture("default-documentation");
+    assert_eq!(
+        findings(&dir, &["--no-deps"]),
+        [
+            "a/Cargo.toml:5: [default-documentation-url] Redundant documentation = \
+          \"https://docs.rs/a\" (default)"
+        ]
+    );
+
+    fix(&dir);
+    assert!(!read(&dir, "a/Cargo.toml").contains("documentation"));
+    assert!(
+        read(&dir, "b/Cargo.toml").contains("documentation = \"https://docs.rs/b/latest/b/api\"")
+    );
+}
+
+#[test]
+fn padded_versions() {
+    let dir = fixture("padded-versions");
+    assert_eq!(
+        findings(&dir, &["--no-deps"]),
+        [
+            "a/Cargo.toml:5: [padded-version-string] Padded rust-version = \"1.70 \"",
+            "a/Cargo.toml:8: [padded-version-string] Padded version \"1 \" for dependency anyhow",
+            "Cargo.toml:5: [padded-version-string] Padded version \" 1.0 \" for dependency serde",
+        ]
+    );
+
+    fix(&dir);
+    assert_eq!(
+        read(&dir, "a/Cargo.toml"),
+        "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\nrust-version = \"1.70\"\n\n\
+         [dependencies]\nanyhow = { version = \"1\" }\nserde.workspace = true\n"
+    );
+    assert!(read(&dir, "Cargo.toml").contains("serde = \"1.0\"\n"));
+}
+
+#[test]
+fn implied_dependency_features() {
+    let dir = fixture("implied-features");
+    assert_eq!(
+        findings(&dir, &["--check-features"]),
+        ["a/Cargo.toml:7: [implied-dependency-features] Feature rt of dependency dep is already \
+          enabled by full"]
+    );
+    // no resolved metadata
+    assert_eq!(
+        findings(&dir, &["--check-features", "--no-deps"]),
+        Vec::<String>::new()
+    );
+
+    let out = ab_lint(&dir, &["--check-features", "--fix", "--allow-dirty"]);
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    assert!(
+        read(&dir, "a/Cargo.toml").contains("dep = { path = \"../dep\", features = [\"full\"] }\n")
+    );
+}
+
+#[test]
+fn dependency_array_of_tables() {
+    let dir = fixture("dependency-array-of-tables");
+    let out = ab_lint(&dir, &["--format", "human-compact"]);
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "a/Cargo.toml:6: [dependency-array-of-tables] dependencies must be a table, not an array \
+         of tables [[dependencies]]\n"
+    );
+    assert!(!String::from_utf8_lossy(&out.stderr).contains("cargo metadata"));
+
+    // configured format
+    let root = dir.join("Cargo.toml");
+    let toml = fs::read_to_string(&root).unwrap();
+    fs::write(
+        &root,
+        toml + "\n[workspace.metadata.ab-lint]\nformat = \"count\"\n",
+    )
+    .unwrap();
+    let out = ab_lint(&dir, &[]);
+    assert!(!out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "1\n");
+}
+
+#[test]
+fn padded_package_version_rejected_by_cargo() {
+    let dir = fixture("padded-package-version");
+    let out = ab_lint(&dir, &["--no-deps", "--format", "human-compact"]);
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "a/Cargo.toml:3: [padded-version-string] Padded version = \" 0.1.0\"\n\
+         a/Cargo.toml:4: [padded-version-string] Padded edition = \"2021 \"\n"
+    );
+}
+
+#[test]
+fn redundant_target_dependencies() {
+    let dir = fixture("redundant-target-deps");
+    assert_eq!(
+        findings(&dir, &["--no-deps"]),
+        [
+            "a/Cargo.toml:10: [redundant-target-dependency] Redundant dependency anyhow in \
+             [target.'cfg(unix)'.dependencies], already in [dependencies]",
+            "a/Cargo.toml:13: [redundant-target-dependency] Redundant dependency anyhow in \
+             [target.\"cfg(windows)\".dependencies], already in [dependencies]",
+        ]
+    );
+
+    fix(&dir);
+    assert_eq!(
+        read(&dir, "a/Cargo.toml"),
+        "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+         [dependencies]\nanyhow = \"1\"\n\n\
+         [target.\"cfg(windows)\".dependencies]\nlog = \"0.4\"\n"
+    );
+}
+
+#[test]
+fn missing_member_manifest() {
+    let dir = fixture("missing-member");
+    let out = ab_lint(&dir, &["--no-deps", "--format", "human-compact"]);
+    assert!(!out.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&out.stdout),
+        "a/Cargo.toml:7: [redundant-optional-false] Redundant optional = false (default) for \
+         dependency anyhow\n\
+         vendor/b/Cargo.toml: [missing-member-manifest] Member manifest not found (submodule not \
+         initialized?)\n"
+    );
+}
+
+#[test]
+fn only_enables_flag_gated_lint() {
+    let dir = fixture("implied-features");
+    assert_eq!(
+        findings(&dir, &["--only", "implied-dependency-features"]),
+        ["a/Cargo.toml:7: [implied-dependency-features] Feature rt of dependency dep is already \
+          enabled by full"]
+    );
+
+    let out = ab_lint(
+        &dir,
+        &["--only", "implied-dependency-features", "--no-deps"],
+    );
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("can't be used with --no-deps"), "{stderr}");
+
+    let out = ab_lint(&dir, &["--only", "dependency-order"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("--only dependency-order needs `dependency-order`"),
+        "{stderr}"
+    );
+}